@@ -0,0 +1,105 @@
+//! Post-game reviews of the games recorded by the server, rendered as text.
+use std::fmt::Write;
+use serde::Deserialize;
+use the_game_core::analysis::{Analysis, PlayReview, TurnReview};
+use the_game_core::event::Event;
+use the_game_core::game::Outcome;
+use the_game_core::variant::Variant;
+
+/// A finished game as served by `get_game`, of which only what it takes to replay it is read.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Record {
+    pub variant: Variant,
+    pub seed: u64,
+    pub log: Vec<Event>,
+}
+
+/// Renders the review as text, one paragraph per turn followed by a table of the players.
+pub fn text(analysis: &Analysis) -> String {
+    let mut out = String::new();
+    let outcome = match analysis.outcome {
+        Some(Outcome::Win) => "won",
+        Some(Outcome::Loss) => "lost",
+        None => "unfinished",
+    };
+    writeln!(out, "Game {}, {} cards left", outcome, analysis.score).unwrap();
+
+    for turn in &analysis.turns {
+        writeln!(out).unwrap();
+        write_turn(&mut out, analysis, turn);
+    }
+
+    writeln!(out).unwrap();
+    writeln!(out, "{:<16} {:>5} {:>5} {:>5} {:>8} {:>9} {:>6} {:>8} {:>7} {:>5}",
+        "player", "turns", "cards", "gap", "gap/card", "backwards", "missed", "optional", "cut_off", "stuck").unwrap();
+    for player in &analysis.players {
+        let average = if player.cards_played == 0 { 0.0 } else { player.gap_spent as f64 / player.cards_played as f64 };
+        writeln!(out, "{:<16} {:>5} {:>5} {:>5} {:>8.2} {:>9} {:>6} {:>8} {:>7} {:>5}",
+            player.name, player.turns, player.cards_played, player.gap_spent, average, player.backwards_tricks,
+            player.missed_backwards_tricks, player.costly_optional_plays, player.cards_cut_off, player.left_stuck).unwrap();
+    }
+
+    out
+}
+
+fn write_turn(out: &mut String, analysis: &Analysis, turn: &TurnReview) {
+    let name = |player: usize| analysis.players.get(player).map_or("?", |player| player.name.as_str());
+    writeln!(out, "Turn {}, {}: gap {}", turn.turn + 1, name(turn.player), turn.gap_spent).unwrap();
+    for play in &turn.plays {
+        writeln!(out, "  {}", describe(play)).unwrap();
+    }
+
+    for trick in &turn.missed_backwards_tricks {
+        writeln!(out, "  missed backwards trick: {} on pile {}", trick.card.0, trick.pile + 1).unwrap();
+    }
+
+    if let Some(player) = turn.left_stuck {
+        writeln!(out, "  left {} unable to play", name(player)).unwrap();
+    }
+}
+
+fn describe(play: &PlayReview) -> String {
+    let mut description = match play.gap {
+        -10 => format!("{} on pile {}, backwards", play.card.0, play.pile + 1),
+        gap => format!("{} on pile {}, gap {}", play.card.0, play.pile + 1, gap),
+    };
+
+    if play.optional {
+        description.push_str(", optional");
+    }
+
+    if play.is_costly_optional() {
+        let cards = play.cut_off.iter().map(|card| card.0.to_string()).collect::<Vec<_>>();
+        write!(description, ", cuts off {}", cards.join(" ")).unwrap();
+    }
+
+    description
+}
+
+#[cfg(test)]
+mod tests {
+    use the_game_core::analysis::analyze;
+    use the_game_core::game::Game;
+    use the_game_core::strategy::{SmallestGap, Strategy};
+    use the_game_core::variant::Variant;
+    use crate::analysis::{text, Record};
+
+    #[test]
+    fn test_text() {
+        let mut game = Game::with_seed(Variant::Standard, 8);
+        game.add_player("Alice");
+        game.add_player("Bob");
+        game.start().unwrap();
+        while game.is_playing() {
+            game.apply(SmallestGap.choose(&game.view())).unwrap();
+        }
+
+        let json = serde_json::json!({ "id": "ignored", "variant": "standard", "seed": 8, "log": game.log() });
+        let record = serde_json::from_value::<Record>(json).unwrap();
+        let analysis = analyze(record.variant, record.seed, &record.log).unwrap();
+        let text = text(&analysis);
+        assert!(text.starts_with(&format!("Game lost, {} cards left\n", game.score())));
+        assert!(text.contains("\nTurn 1, Alice: gap "));
+        assert_eq!(text.lines().filter(|line| line.starts_with("Alice ") || line.starts_with("Bob ")).count(), 2);
+    }
+}
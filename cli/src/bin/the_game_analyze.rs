@@ -0,0 +1,45 @@
+//! Reviews a finished game turn by turn, pointing out the costly plays.
+use std::io::{self, Read};
+use std::fs;
+use std::path::PathBuf;
+use clap::{Parser, ValueEnum};
+use the_game_cli::analysis::{self, Record};
+use the_game_core::analysis::analyze;
+
+/// Reviews a finished game turn by turn: the gap spent, the missed backwards tricks,
+/// the moves that left a player unable to play and the optional plays that cut off cards.
+#[derive(Debug, Parser)]
+struct Args {
+    /// A JSON file holding the game, as served by `GET /games/{id}`, or `-` for the standard input.
+    game: PathBuf,
+    /// The format of the report.
+    #[arg(long, short, default_value = "text")]
+    format: Format,
+}
+
+/// The format of the report.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Format {
+    Text,
+    Json,
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = Args::parse();
+    let json = if args.game.as_os_str() == "-" {
+        let mut json = String::new();
+        io::stdin().read_to_string(&mut json)?;
+        json
+    } else {
+        fs::read_to_string(&args.game)?
+    };
+
+    let record = serde_json::from_str::<Record>(&json)?;
+    let analysis = analyze(record.variant, record.seed, &record.log)?;
+    match args.format {
+        Format::Text => print!("{}", analysis::text(&analysis)),
+        Format::Json => println!("{}", serde_json::to_string_pretty(&analysis)?),
+    }
+
+    Ok(())
+}
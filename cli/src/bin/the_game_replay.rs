@@ -0,0 +1,86 @@
+//! Steps through a finished game in the terminal, move by move.
+use std::fs;
+use std::io::{self, BufRead, Read, Write};
+use std::path::PathBuf;
+use clap::Parser;
+use the_game_cli::replay::{self, Replay};
+
+/// Steps through a finished game in the terminal, move by move.
+/// Press enter or `n` to go forward, `b` to go back, `t <turn>` to jump to the start of a turn,
+/// `s` and `e` to go to the start and the end, and `q` to quit.
+#[derive(Debug, Parser)]
+struct Args {
+    /// The game, as the JSON served by `GET /games/{id}` or in the notation, or `-` for the standard input.
+    game: PathBuf,
+    /// Only shows the hand of this player, counted from 1, the others being counted.
+    #[arg(long, short)]
+    player: Option<usize>,
+    /// The turn to start from, counted from 1.
+    #[arg(long, short, default_value_t = 1)]
+    turn: usize,
+    /// Prints the notation of the game instead of stepping through it.
+    #[arg(long)]
+    notation: bool,
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = Args::parse();
+    let text = if args.game.as_os_str() == "-" {
+        let mut text = String::new();
+        io::stdin().read_to_string(&mut text)?;
+        text
+    } else {
+        fs::read_to_string(&args.game)?
+    };
+
+    let notation = replay::read(&text)?;
+    if args.notation {
+        print!("{}", notation);
+        return Ok(());
+    }
+
+    let replay = Replay::new(&notation)?;
+    let viewer = args.player
+        .map(|player| (1..=notation.players.len()).contains(&player).then(|| player - 1).ok_or("There is no such player"))
+        .transpose()?;
+    let mut index = replay.turn_start(args.turn).ok_or("There is no such turn")?;
+    // The commands are read from the terminal when the game comes through the standard input.
+    let input: Option<Box<dyn BufRead>> = if args.game.as_os_str() == "-" {
+        fs::File::open("/dev/tty").ok().map(|tty| Box::new(io::BufReader::new(tty)) as Box<dyn BufRead>)
+    } else {
+        Some(Box::new(io::stdin().lock()))
+    };
+    let mut commands = input.map(BufRead::lines);
+    loop {
+        let step = replay.step(index).expect("the index stays within the replay");
+        print!("\n[{}/{}]\n{}", index, replay.len() - 1, replay::render(step, viewer));
+
+        let Some(command) = commands.as_mut().and_then(|lines| {
+            print!("> ");
+            io::stdout().flush().ok()?;
+            lines.next()?.ok()
+        }) else {
+            return Ok(());
+        };
+
+        let mut words = command.split_whitespace();
+        index = match (words.next(), words.next().map(str::parse::<usize>)) {
+            (None | Some("n"), _) => (index + 1).min(replay.len() - 1),
+            (Some("b"), _) => index.saturating_sub(1),
+            (Some("s"), _) => 0,
+            (Some("e"), _) => replay.len() - 1,
+            (Some("t"), Some(Ok(turn))) => match replay.turn_start(turn) {
+                Some(start) => start,
+                None => {
+                    println!("There is no turn {}", turn);
+                    index
+                }
+            },
+            (Some("q"), _) => return Ok(()),
+            _ => {
+                println!("Commands: enter or n, b, t <turn>, s, e, q");
+                index
+            }
+        };
+    }
+}
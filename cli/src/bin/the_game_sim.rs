@@ -0,0 +1,58 @@
+//! Plays seeded games between bots and reports how well each strategy does.
+use clap::{Parser, ValueEnum};
+use the_game_cli::simulation::{self, Simulation};
+use the_game_core::strategy::BuiltinStrategy;
+use the_game_core::variant::Variant;
+
+/// Plays seeded games between bots and reports how well each strategy does
+/// for each number of players.
+#[derive(Debug, Parser)]
+struct Args {
+    /// The number of games played for each strategy and number of players.
+    #[arg(long, short, default_value_t = 100)]
+    games: usize,
+    /// The seed of the first game, the next ones are seeded with the following numbers.
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+    /// The numbers of players, from 1 to 5.
+    #[arg(long, short, value_delimiter = ',', default_value = "1,2,3,4,5", value_parser = clap::value_parser!(u8).range(1..=5))]
+    players: Vec<u8>,
+    /// The strategies, each of which plays every seat of its games.
+    #[arg(long, short, value_delimiter = ',', default_value = "random,smallest_gap,backwards_trick")]
+    strategies: Vec<BuiltinStrategy>,
+    /// The rules the games are played with.
+    #[arg(long, default_value_t = Variant::Standard)]
+    variant: Variant,
+    /// The format of the report.
+    #[arg(long, short, default_value = "table")]
+    format: Format,
+}
+
+/// The format of the report.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Format {
+    /// An aligned table, with losses counted as blocked/blocked in the endgame/illegal decision.
+    Table,
+    Csv,
+    Json,
+}
+
+fn main() -> serde_json::Result<()> {
+    let args = Args::parse();
+    let simulation = Simulation {
+        variant: args.variant,
+        games: args.games,
+        seed: args.seed,
+        players: args.players.into_iter().map(usize::from).collect(),
+        strategies: args.strategies,
+    };
+
+    let rows = simulation.run();
+    match args.format {
+        Format::Table => print!("{}", simulation::table(&rows)),
+        Format::Csv => print!("{}", simulation::csv(&rows)),
+        Format::Json => println!("{}", serde_json::to_string_pretty(&rows)?),
+    }
+
+    Ok(())
+}
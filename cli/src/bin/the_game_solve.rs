@@ -0,0 +1,57 @@
+//! Solves seeded single-player games, telling whether they can be won and how.
+use clap::Parser;
+use the_game_core::game::Game;
+use the_game_core::solver::Solver;
+use the_game_core::strategy::Decision;
+use the_game_core::variant::Variant;
+
+/// Solves seeded single-player games, telling whether they can be won
+/// and printing the best line of play found.
+#[derive(Debug, Parser)]
+struct Args {
+    /// The seeds of the games.
+    #[arg(required = true)]
+    seeds: Vec<u64>,
+    /// The rules the games are played with.
+    #[arg(long, default_value_t = Variant::Standard)]
+    variant: Variant,
+    /// The most positions looked at per game before settling for the best line found so far, `0` for no limit.
+    #[arg(long, default_value_t = 10_000_000)]
+    node_limit: u64,
+    /// Prints the decisions of the best line.
+    #[arg(long)]
+    line: bool,
+}
+
+fn main() {
+    let args = Args::parse();
+    let solver = Solver { node_limit: Some(args.node_limit).filter(|limit| *limit > 0) };
+    for seed in args.seeds {
+        let mut game = Game::with_seed(args.variant, seed);
+        game.add_player("Solo");
+        game.start().expect("a new game can be started");
+
+        let solution = solver.solve(&game).expect("a new single-player game can be solved");
+        let verdict = match solution.winnable() {
+            Some(true) => "winnable",
+            Some(false) => "not winnable",
+            None => "unknown",
+        };
+
+        println!("seed {}: {}, best score {}, {} positions", seed, verdict, solution.score, solution.nodes);
+        if args.line {
+            // Plays are written as in the notation of games, such as `37^1`, naming the piles from 1 to 4.
+            let piles = game.board().discard_piles();
+            let turns = solution.line.split_inclusive(|decision| *decision == Decision::EndTurn);
+            for (turn, decisions) in turns.enumerate() {
+                let plays = decisions.iter()
+                    .filter_map(|decision| match decision {
+                        Decision::Play { pile, card } => Some(format!("{}{}{}", card, piles[*pile].direction(), pile + 1)),
+                        Decision::EndTurn => None,
+                    })
+                    .collect::<Vec<_>>();
+                println!("  T{} {}", turn + 1, plays.join(" "));
+            }
+        }
+    }
+}
@@ -0,0 +1,4 @@
+//! Command line tools running the game engine directly, without a server.
+pub mod analysis;
+pub mod replay;
+pub mod simulation;
@@ -0,0 +1,171 @@
+//! Finished games rebuilt move by move, for stepping through them in a terminal.
+use std::error::Error;
+use std::fmt::Write;
+use the_game_core::game::{Game, Outcome};
+use the_game_core::notation::{Ending, Notation};
+use crate::analysis::Record;
+
+/// The state of the game after one move.
+#[derive(Debug, Clone)]
+pub struct Step {
+    pub game: Game,
+    /// What the move leading to this state was, `None` for the opening deal.
+    pub last: Option<String>,
+    /// The moves of the turn being played, up to this state.
+    pub turn_moves: Vec<String>,
+}
+
+/// Every state a game went through, from the opening deal to its last move.
+#[derive(Debug, Clone)]
+pub struct Replay {
+    steps: Vec<Step>,
+}
+
+/// Reads a game from the JSON served by `GET /games/{id}`, or else from its notation.
+pub fn read(text: &str) -> Result<Notation, Box<dyn Error>> {
+    if text.trim_start().starts_with('{') {
+        let record = serde_json::from_str::<Record>(text)?;
+        return Ok(Notation::from_log(record.variant, record.seed, &record.log)?);
+    }
+
+    Ok(text.parse()?)
+}
+
+impl Replay {
+    /// Replays the game of the notation from its seed, keeping the state after every move.
+    pub fn new(notation: &Notation) -> Result<Self, &'static str> {
+        let mut game = Game::with_seed(notation.variant, notation.seed);
+        for name in &notation.players {
+            game.add_player(name.clone());
+        }
+
+        game.start()?;
+        let mut steps = vec![Step { game: game.clone(), last: None, turn_moves: Vec::new() }];
+        for line in &notation.turns {
+            if !game.is_playing() || line.turn != game.turn || line.player != game.current_player_index {
+                return Err("The turns don't match the game");
+            }
+
+            let name = notation.players[line.player].clone();
+            let mut turn_moves = Vec::new();
+            for (pile, card) in &line.plays {
+                game.discard_card(*pile, card)?;
+                turn_moves.push(format!("{}{}{}", card, game.board().discard_piles()[*pile].direction(), pile + 1));
+                let last = format!("{} played {} onto pile {}", name, card, pile + 1);
+                steps.push(Step { game: game.clone(), last: Some(last), turn_moves: turn_moves.clone() });
+            }
+
+            let last = match line.ending {
+                Some(Ending::Ended) => {
+                    let drawn = game.end_turn()?.len();
+                    format!("{} ended turn {} and drew {} cards", name, line.turn + 1, drawn)
+                }
+                Some(Ending::Forfeited) => {
+                    game.forfeit()?;
+                    format!("{} gave up the game", name)
+                }
+                None => continue,
+            };
+
+            steps.push(Step { game: game.clone(), last: Some(last), turn_moves: Vec::new() });
+        }
+
+        Ok(Self { steps })
+    }
+
+    /// Returns the number of states, the opening deal included.
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    /// Returns `true` if there are no states, which never happens once replayed.
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// Returns the state at the given index, `0` being the opening deal.
+    pub fn step(&self, index: usize) -> Option<&Step> {
+        self.steps.get(index)
+    }
+
+    /// Returns the index of the state the given turn starts from, counting turns from 1.
+    pub fn turn_start(&self, turn: usize) -> Option<usize> {
+        self.steps.iter().position(|step| step.game.turn + 1 == turn)
+    }
+}
+
+/// Renders a state: the piles and the deck, the hands, and the moves of the turn.
+/// Only the hand of the given player is shown if there is one, the others being counted.
+pub fn render(step: &Step, viewer: Option<usize>) -> String {
+    let game = &step.game;
+    let mut out = String::new();
+    let current = game.get_current_player();
+    match game.outcome() {
+        Some(Outcome::Win) => writeln!(out, "Game won").unwrap(),
+        Some(Outcome::Loss) => writeln!(out, "Game lost, {} cards left", game.score()).unwrap(),
+        None => writeln!(out, "Turn {}, {} to play, {} of {} cards played", game.turn + 1, current.name, game.actions_taken, game.get_remaining_actions()).unwrap(),
+    }
+
+    if let Some(last) = &step.last {
+        writeln!(out, "Last move: {}", last).unwrap();
+    }
+
+    if !step.turn_moves.is_empty() {
+        writeln!(out, "This turn: {}", step.turn_moves.join(" ")).unwrap();
+    }
+
+    writeln!(out, "{}", game.board()).unwrap();
+    let width = game.players().iter().map(|player| player.name.len()).max().unwrap_or_default();
+    for (seat, player) in game.players().iter().enumerate() {
+        let marker = if seat == game.current_player_index && game.is_playing() { '*' } else { ' ' };
+        let hand = match viewer {
+            Some(viewer) if viewer != seat => format!("{} cards", player.hand.len()),
+            _ => player.hand.to_string(),
+        };
+        writeln!(out, "{} {:<width$}  {}", marker, player.name, hand).unwrap();
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use the_game_core::event::Event;
+    use the_game_core::game::Game;
+    use the_game_core::notation::Notation;
+    use the_game_core::strategy::{BackwardsTrick, Strategy};
+    use the_game_core::variant::Variant;
+    use crate::replay::{read, render, Replay};
+
+    #[test]
+    fn test_replay() {
+        let mut game = Game::with_seed(Variant::Standard, 3);
+        game.add_player("Alice");
+        game.add_player("Bob");
+        game.start().unwrap();
+        while game.is_playing() {
+            game.apply(BackwardsTrick.choose(&game.view())).unwrap();
+        }
+
+        let notation = Notation::from_game(&game).unwrap();
+        let json = serde_json::json!({ "variant": "standard", "seed": 3, "log": game.log() }).to_string();
+        assert_eq!(read(&json).unwrap(), notation);
+        assert_eq!(read(&notation.to_string()).unwrap(), notation);
+
+        let replay = Replay::new(&notation).unwrap();
+        let moves = game.log().iter().filter(|event| matches!(event, Event::Discarded { .. } | Event::TurnEnded { .. })).count();
+        assert_eq!(replay.len(), moves + 1);
+        assert_eq!(replay.step(replay.len() - 1).unwrap().game.score(), game.score());
+        assert_eq!(replay.turn_start(1), Some(0));
+        assert_eq!(replay.step(replay.turn_start(2).unwrap()).unwrap().game.turn, 1);
+
+        let first = render(replay.step(0).unwrap(), Some(0));
+        assert!(first.starts_with("Turn 1, Alice to play, 0 of 2 cards played\n1: 1^  2: 1^  3: 100v  4: 100v  deck 84\n"));
+        assert!(first.contains("\n  Bob    7 cards\n"));
+
+        let second = render(replay.step(1).unwrap(), None);
+        assert!(second.contains("Last move: Alice played "));
+        assert!(second.contains("\nThis turn: "));
+        assert!(!second.contains("7 cards"));
+    }
+}
@@ -0,0 +1,247 @@
+//! Seeded games played by bots, summed up per strategy and number of players.
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use serde::Serialize;
+use the_game_core::game::{Game, Outcome};
+use the_game_core::strategy::BuiltinStrategy;
+use the_game_core::variant::Variant;
+
+/// Why a game was lost.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LossCause {
+    /// A player couldn't play the cards the turn requires while the deck had cards.
+    Blocked,
+    /// A player couldn't play a card once the deck was empty.
+    BlockedEndgame,
+    /// A bot made a decision the rules don't allow.
+    IllegalDecision,
+}
+
+/// The games to play.
+#[derive(Debug, Clone)]
+pub struct Simulation {
+    pub variant: Variant,
+    /// The number of games played for each strategy and number of players.
+    pub games: usize,
+    /// The seed of the first game, the next ones are seeded with the following numbers.
+    pub seed: u64,
+    /// The numbers of players, from 1 to 5.
+    pub players: Vec<usize>,
+    /// The strategies, each of which plays every seat of its games.
+    pub strategies: Vec<BuiltinStrategy>,
+}
+
+/// The result of a single game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GameResult {
+    pub outcome: Outcome,
+    /// The number of cards left unplayed.
+    pub score: usize,
+    /// The number of turns played.
+    pub turns: usize,
+    pub loss_cause: Option<LossCause>,
+}
+
+/// The results of the games of one strategy with one number of players.
+#[derive(Debug, Clone, Serialize)]
+pub struct Row {
+    #[serde(serialize_with = "serialize_display")]
+    pub strategy: BuiltinStrategy,
+    pub players: usize,
+    pub games: usize,
+    pub wins: usize,
+    pub win_rate: f64,
+    pub average_score: f64,
+    pub scores: Quantiles,
+    pub average_turns: f64,
+    /// The number of games lost, by cause.
+    pub losses: BTreeMap<LossCause, usize>,
+}
+
+/// The distribution of the scores.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Quantiles {
+    pub min: usize,
+    pub p25: usize,
+    pub median: usize,
+    pub p75: usize,
+    pub max: usize,
+}
+
+impl Simulation {
+    /// Plays every game, returning one row per strategy and number of players.
+    pub fn run(&self) -> Vec<Row> {
+        let mut rows = Vec::new();
+        for strategy in &self.strategies {
+            for players in &self.players {
+                let results = (0..self.games as u64)
+                    .map(|game| play(self.variant, self.seed.wrapping_add(game), *players, *strategy))
+                    .collect::<Vec<_>>();
+                rows.push(Row::new(*strategy, *players, &results));
+            }
+        }
+
+        rows
+    }
+}
+
+/// Plays a seeded game with every seat played by the given strategy.
+pub fn play(variant: Variant, seed: u64, players: usize, strategy: BuiltinStrategy) -> GameResult {
+    let mut game = Game::with_seed(variant, seed);
+    for seat in 0..players {
+        game.add_player(format!("Bot {}", seat + 1));
+    }
+
+    let bots = (0..players as u64).map(|seat| strategy.build(seed ^ seat)).collect::<Vec<_>>();
+    game.start().expect("a new game can be started");
+    while game.is_playing() {
+        let decision = bots[game.current_player_index].choose(&game.view());
+        if game.apply(decision).is_err() {
+            let loss_cause = Some(LossCause::IllegalDecision);
+            return GameResult { outcome: Outcome::Loss, score: game.score(), turns: game.turn + 1, loss_cause };
+        }
+    }
+
+    let outcome = game.outcome().expect("the game is over");
+    let loss_cause = match outcome {
+        Outcome::Win => None,
+        Outcome::Loss if game.board().deck.is_empty() => Some(LossCause::BlockedEndgame),
+        Outcome::Loss => Some(LossCause::Blocked),
+    };
+
+    GameResult { outcome, score: game.score(), turns: game.turn + 1, loss_cause }
+}
+
+impl Row {
+    /// Sums up the given results.
+    pub fn new(strategy: BuiltinStrategy, players: usize, results: &[GameResult]) -> Self {
+        let games = results.len();
+        let average = |total: usize| if games == 0 { 0.0 } else { total as f64 / games as f64 };
+        let wins = results.iter().filter(|result| result.outcome == Outcome::Win).count();
+        let mut scores = results.iter().map(|result| result.score).collect::<Vec<_>>();
+        scores.sort_unstable();
+        let quantile = |q: f64| scores.get(((scores.len().saturating_sub(1)) as f64 * q).round() as usize).copied().unwrap_or_default();
+
+        let mut losses = BTreeMap::new();
+        for cause in results.iter().filter_map(|result| result.loss_cause) {
+            *losses.entry(cause).or_default() += 1;
+        }
+
+        Self {
+            strategy,
+            players,
+            games,
+            wins,
+            win_rate: average(wins),
+            average_score: average(scores.iter().sum()),
+            scores: Quantiles { min: quantile(0.0), p25: quantile(0.25), median: quantile(0.5), p75: quantile(0.75), max: quantile(1.0) },
+            average_turns: average(results.iter().map(|result| result.turns).sum()),
+            losses,
+        }
+    }
+
+    /// Returns the number of games lost for the given reason.
+    pub fn losses(&self, cause: LossCause) -> usize {
+        self.losses.get(&cause).copied().unwrap_or_default()
+    }
+}
+
+/// The columns of the table and of the CSV output.
+const COLUMNS: [&str; 13] = [
+    "strategy", "players", "games", "wins", "win_rate", "avg_score", "min", "p25", "median", "p75", "max", "avg_turns", "losses",
+];
+
+/// Returns the fields of a row, in the order of `COLUMNS`, with the losses by cause in the last one.
+fn fields(row: &Row) -> [String; 13] {
+    let losses = [LossCause::Blocked, LossCause::BlockedEndgame, LossCause::IllegalDecision]
+        .map(|cause| row.losses(cause).to_string())
+        .join("/");
+
+    [
+        row.strategy.to_string(),
+        row.players.to_string(),
+        row.games.to_string(),
+        row.wins.to_string(),
+        format!("{:.3}", row.win_rate),
+        format!("{:.2}", row.average_score),
+        row.scores.min.to_string(),
+        row.scores.p25.to_string(),
+        row.scores.median.to_string(),
+        row.scores.p75.to_string(),
+        row.scores.max.to_string(),
+        format!("{:.1}", row.average_turns),
+        losses,
+    ]
+}
+
+/// Renders the rows as an aligned table, the losses being counted as blocked/blocked in the endgame/illegal.
+pub fn table(rows: &[Row]) -> String {
+    let cells = rows.iter().map(fields).collect::<Vec<_>>();
+    let widths = (0..COLUMNS.len())
+        .map(|column| cells.iter().map(|row| row[column].len()).fold(COLUMNS[column].len(), usize::max))
+        .collect::<Vec<_>>();
+
+    let mut out = String::new();
+    let mut line = |values: Vec<&str>| {
+        // The strategy is aligned to the left, the numbers to the right.
+        let padded = values.iter().zip(&widths).enumerate()
+            .map(|(column, (value, width))| if column == 0 { format!("{:<width$}", value) } else { format!("{:>width$}", value) })
+            .collect::<Vec<_>>();
+        writeln!(out, "{}", padded.join("  ").trim_end()).unwrap();
+    };
+
+    line(COLUMNS.to_vec());
+    for row in &cells {
+        line(row.iter().map(String::as_str).collect());
+    }
+
+    out
+}
+
+/// Renders the rows as CSV, with one column per loss cause.
+pub fn csv(rows: &[Row]) -> String {
+    let mut out = COLUMNS[..COLUMNS.len() - 1].join(",");
+    out.push_str(",blocked,blocked_endgame,illegal_decision\n");
+    for row in rows {
+        let fields = fields(row);
+        let losses = fields[COLUMNS.len() - 1].replace('/', ",");
+        writeln!(out, "{},{}", fields[..COLUMNS.len() - 1].join(","), losses).unwrap();
+    }
+
+    out
+}
+
+fn serialize_display<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error> where T: std::fmt::Display, S: serde::Serializer {
+    serializer.collect_str(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use the_game_core::strategy::BuiltinStrategy;
+    use the_game_core::variant::Variant;
+    use crate::simulation::{csv, play, table, Simulation};
+
+    #[test]
+    fn test_simulation() {
+        let simulation = Simulation {
+            variant: Variant::Standard,
+            games: 5,
+            seed: 1,
+            players: vec![1, 3],
+            strategies: vec![BuiltinStrategy::Random, BuiltinStrategy::BackwardsTrick],
+        };
+
+        let rows = simulation.run();
+        assert_eq!(rows.len(), 4);
+        for row in &rows {
+            assert_eq!(row.games, 5);
+            assert_eq!(row.wins + row.losses.values().sum::<usize>(), 5);
+            assert!(row.scores.min <= row.scores.median && row.scores.median <= row.scores.max);
+        }
+
+        assert_eq!(play(Variant::Standard, 3, 2, BuiltinStrategy::SmallestGap), play(Variant::Standard, 3, 2, BuiltinStrategy::SmallestGap));
+        assert_eq!(table(&rows).lines().count(), 5);
+        assert_eq!(csv(&rows).lines().next().unwrap().split(',').count(), 15);
+    }
+}
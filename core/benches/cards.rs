@@ -0,0 +1,52 @@
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use the_game_core::game::Game;
+use the_game_core::strategy::{BackwardsTrick, Strategy};
+use the_game_core::variant::Variant;
+
+/// Returns a game of three players, a few turns in.
+fn midgame() -> Game {
+    let mut game = Game::with_seed(Variant::Standard, 7);
+    for name in ["Alice", "Bob", "Carol"] {
+        game.add_player(name);
+    }
+
+    game.start().unwrap();
+    while game.turn < 6 {
+        game.apply(BackwardsTrick.choose(&game.view())).unwrap();
+    }
+
+    game
+}
+
+/// Measures the operations on hands that searches repeat the most.
+fn hands(c: &mut Criterion) {
+    let game = midgame();
+    let player = game.get_current_player();
+    let card = player.hand()[0];
+
+    c.bench_function("hand_clone", |b| b.iter(|| black_box(&player.hand).clone()));
+    c.bench_function("hand_remove", |b| {
+        b.iter_batched_ref(|| player.hand.clone(), |hand| hand.remove(black_box(&card)), BatchSize::SmallInput)
+    });
+    c.bench_function("can_play_a_card", |b| b.iter(|| black_box(player).can_play_a_card(black_box(game.board()))));
+}
+
+fn games(c: &mut Criterion) {
+    let game = midgame();
+    c.bench_function("game_clone", |b| b.iter(|| black_box(&game).clone()));
+    c.bench_function("plays", |b| b.iter(|| black_box(&game).view().plays().count()));
+    c.bench_function("sample", |b| {
+        let view = game.view();
+        let mut rng = <rand::rngs::StdRng as rand::SeedableRng>::seed_from_u64(0);
+        b.iter(|| Game::sample(black_box(&view), &mut rng))
+    });
+    c.bench_function("resample", |b| {
+        let view = game.view();
+        let mut rng = <rand::rngs::StdRng as rand::SeedableRng>::seed_from_u64(0);
+        let mut sample = Game::sample(&view, &mut rng);
+        b.iter(|| sample.resample(black_box(&view), &mut rng))
+    });
+}
+
+criterion_group!(benches, hands, games);
+criterion_main!(benches);
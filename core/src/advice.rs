@@ -0,0 +1,84 @@
+use serde::{Deserialize, Serialize};
+use crate::strategy::{Decision, Play, PlayerView};
+
+/// A decision suggested to the player, with why it is suggested.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Suggestion {
+    pub decision: Decision,
+    /// How many ranks the decision gives up, `-10` for a card played backwards by ten. Lower is better.
+    pub cost: i8,
+    /// A short explanation for the player, naming the piles from 1 to 4.
+    pub explanation: String,
+}
+
+/// Returns every legal decision of the player, the best first.
+/// Plays are ranked by the ranks they skip, and once the turn has the cards it requires,
+/// ending it is ranked above every play skipping a rank.
+pub fn advise(view: &PlayerView) -> Vec<Suggestion> {
+    let mut plays = view.plays().collect::<Vec<_>>();
+    plays.sort_by_key(|play| (play.gap, play.pile, play.card.0));
+    let smallest = plays.iter().find(|play| play.gap > 0).copied();
+
+    let mut suggestions = plays.into_iter()
+        .map(|play| Suggestion { decision: play.into(), cost: play.gap, explanation: explain(view, &play, smallest == Some(play)) })
+        .collect::<Vec<_>>();
+
+    if view.can_end_turn() {
+        let explanation = match view.required_actions {
+            1 => "the card the turn requires is played, the others can wait".to_string(),
+            required => format!("the {} cards the turn requires are played, the others can wait", required),
+        };
+
+        let position = suggestions.iter().position(|suggestion| suggestion.cost > 0).unwrap_or(suggestions.len());
+        suggestions.insert(position, Suggestion { decision: Decision::EndTurn, cost: 1, explanation });
+    }
+
+    suggestions
+}
+
+fn explain(view: &PlayerView, play: &Play, smallest: bool) -> String {
+    let pile = play.pile + 1;
+    let explanation = match play.gap {
+        -10 => format!("backwards trick available on pile {}", pile),
+        0 => format!("follows the top of pile {} exactly", pile),
+        gap if smallest => format!("smallest gap {} on pile {}", gap, pile),
+        gap => format!("gap {} on pile {}", gap, pile),
+    };
+
+    if view.can_end_turn() && play.gap > 0 {
+        format!("{}, beyond what the turn requires", explanation)
+    } else {
+        explanation
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::advice::advise;
+    use crate::card::Card;
+    use crate::game::Game;
+    use crate::hand::Hand;
+    use crate::strategy::Decision;
+
+    #[test]
+    fn test_advise() {
+        let mut game = Game::new();
+        game.add_player("Alice");
+        game.start().unwrap();
+        game.players[0].hand = Hand::from_iter([Card(30), Card(2), Card(97), Card(20), Card(60)]);
+
+        let suggestions = advise(&game.view());
+        assert_eq!(suggestions.len(), game.view().plays().count());
+        assert_eq!(suggestions[0].decision, Decision::Play { pile: 0, card: Card(2) });
+        assert_eq!(suggestions[0].explanation, "follows the top of pile 1 exactly");
+        assert_eq!(suggestions[2].explanation, "smallest gap 2 on pile 3");
+
+        game.discard_card(0, &Card(30)).unwrap();
+        game.discard_card(1, &Card(60)).unwrap();
+        let suggestions = advise(&game.view());
+        assert_eq!(suggestions[0].explanation, "backwards trick available on pile 1");
+        assert_eq!(suggestions[1].decision, Decision::EndTurn);
+        assert_eq!(suggestions[2].decision, Decision::Play { pile: 2, card: Card(97) });
+        assert_eq!(suggestions[2].explanation, "smallest gap 2 on pile 3, beyond what the turn requires");
+    }
+}
@@ -0,0 +1,250 @@
+use serde::{Deserialize, Serialize};
+use crate::card::Card;
+use crate::card_set::CardSet;
+use crate::discard_pile::DiscardPile;
+use crate::event::Event;
+use crate::game::{Game, Outcome};
+use crate::variant::Variant;
+
+/// A review of a finished game, drawn from its log.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Analysis {
+    pub turns: Vec<TurnReview>,
+    /// One summary per player, in seating order.
+    pub players: Vec<PlayerReview>,
+    /// How the game ended, `None` if the log stops before the end.
+    pub outcome: Option<Outcome>,
+    /// The number of cards left unplayed.
+    pub score: usize,
+}
+
+/// What a player did on one of their turns.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct TurnReview {
+    pub turn: usize,
+    pub player: usize,
+    pub plays: Vec<PlayReview>,
+    /// The ranks skipped by the plays of the turn, cards played backwards by ten counting for nothing.
+    pub gap_spent: u32,
+    /// The cards the player could have played backwards by ten during the turn, and kept instead.
+    pub missed_backwards_tricks: Vec<Trick>,
+    /// The player left unable to play the cards their turn requires by the last move of this turn, if any.
+    pub left_stuck: Option<usize>,
+}
+
+/// A card played, and what it cost.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct PlayReview {
+    pub card: Card,
+    pub pile: usize,
+    /// The ranks skipped, `-10` for a card played backwards by ten.
+    pub gap: i8,
+    /// Whether the turn already had the cards it requires.
+    pub optional: bool,
+    /// The cards still in the game, in a hand or in the deck, that can no longer follow on the pile.
+    #[cfg_attr(feature = "schemars", schemars(with = "Vec<Card>"))]
+    pub cut_off: CardSet,
+}
+
+/// A card that could be played backwards by ten onto a pile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Trick {
+    pub card: Card,
+    pub pile: usize,
+}
+
+/// The totals of one player over the game.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct PlayerReview {
+    pub name: String,
+    pub turns: usize,
+    pub cards_played: usize,
+    pub gap_spent: u32,
+    pub backwards_tricks: usize,
+    pub missed_backwards_tricks: usize,
+    /// The plays made beyond what the turn required that cut off cards still in the game.
+    pub costly_optional_plays: usize,
+    /// The cards cut off by those plays.
+    pub cards_cut_off: usize,
+    /// The number of times a move of the player left someone unable to play.
+    pub left_stuck: usize,
+}
+
+impl PlayReview {
+    /// Returns `true` if the play wasn't required and cut off cards still in the game.
+    pub fn is_costly_optional(&self) -> bool {
+        self.optional && !self.cut_off.is_empty()
+    }
+}
+
+/// Replays the log of a game played with the given rules and seed, reviewing every turn.
+/// Fails if the log doesn't start with the game or doesn't match the deal of the seed.
+pub fn analyze(variant: Variant, seed: u64, log: &[Event]) -> Result<Analysis, &'static str> {
+    let mut game = Game::with_seed(variant, seed);
+    match log.first() {
+        Some(Event::Started { players }) => players.iter().for_each(|name| game.add_player(name.clone())),
+        _ => return Err("The log doesn't start with the game"),
+    }
+
+    game.start()?;
+    review(game, &log[1..])
+}
+
+/// Replays the given events onto a started game, reviewing every turn.
+fn review(mut game: Game, log: &[Event]) -> Result<Analysis, &'static str> {
+    let mut turns = Vec::<TurnReview>::new();
+    let mut tricks = Vec::new();
+    for event in log {
+        let (turn, player) = match event {
+            Event::Discarded { turn, player, .. } | Event::TurnEnded { turn, player, .. } | Event::Forfeited { turn, player } => (*turn, *player),
+            _ => continue,
+        };
+
+        if !game.is_playing() || turn != game.turn || player != game.current_player_index {
+            return Err("The log doesn't match the game");
+        }
+
+        if turns.last().is_none_or(|review| review.turn != turn) {
+            turns.push(TurnReview { turn, player, plays: Vec::new(), gap_spent: 0, missed_backwards_tricks: Vec::new(), left_stuck: None });
+            tricks.clear();
+        }
+
+        add_tricks(&game, &mut tricks);
+        let review = turns.last_mut().expect("the turn was just reviewed");
+        match event {
+            Event::Discarded { card, pile, .. } => {
+                let discard_pile = game.board().discard_piles().get(*pile).ok_or("The log doesn't match the game")?;
+                let gap = discard_pile.gap(card).ok_or("The log doesn't match the game")?;
+                let in_game = game.players().iter().fold(game.board().deck.cards().iter().collect(), |cards: CardSet, player| cards | player.hand.card_set());
+                let mut cut_off = (discard_pile.playable_cards() - after(discard_pile, card)) & in_game;
+                cut_off.remove(card);
+                let optional = game.can_end_turn();
+                game.discard_card(*pile, card)?;
+                review.gap_spent += gap.max(0) as u32;
+                review.plays.push(PlayReview { card: *card, pile: *pile, gap, optional, cut_off });
+                add_tricks(&game, &mut tricks);
+            }
+            Event::TurnEnded { .. } => {
+                game.end_turn()?;
+            }
+            _ => {
+                game.forfeit()?;
+            }
+        }
+
+        review.missed_backwards_tricks = tricks.iter().copied().filter(|trick| game.players()[player].hand.contains(&trick.card)).collect();
+        if game.outcome() == Some(Outcome::Loss) && !matches!(event, Event::Forfeited { .. }) {
+            review.left_stuck = Some(game.current_player_index);
+        }
+    }
+
+    let mut players = game.players().iter().map(|player| PlayerReview { name: player.name.clone(), ..PlayerReview::default() }).collect::<Vec<_>>();
+    for review in &turns {
+        let summary = &mut players[review.player];
+        summary.turns += 1;
+        summary.cards_played += review.plays.len();
+        summary.gap_spent += review.gap_spent;
+        summary.backwards_tricks += review.plays.iter().filter(|play| play.gap == -10).count();
+        summary.missed_backwards_tricks += review.missed_backwards_tricks.len();
+        summary.left_stuck += usize::from(review.left_stuck.is_some());
+        for play in review.plays.iter().filter(|play| play.is_costly_optional()) {
+            summary.costly_optional_plays += 1;
+            summary.cards_cut_off += play.cut_off.len();
+        }
+    }
+
+    Ok(Analysis { turns, players, outcome: game.outcome(), score: game.score() })
+}
+
+/// Adds the backwards tricks the current player has to the given ones, once per card.
+fn add_tricks(game: &Game, tricks: &mut Vec<Trick>) {
+    for (pile, discard_pile) in game.board().discard_piles().iter().enumerate() {
+        for &card in game.get_current_player().hand() {
+            if discard_pile.gap(&card) == Some(-10) && tricks.iter().all(|trick| trick.card != card) {
+                tricks.push(Trick { card, pile });
+            }
+        }
+    }
+}
+
+/// Returns the cards that can still follow on the pile once the given card is played onto it.
+fn after(pile: &DiscardPile, card: &Card) -> CardSet {
+    let mut pile = pile.clone();
+    match pile.discard_card(card) {
+        Ok(()) => pile.playable_cards(),
+        Err(_) => CardSet::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::{analyze, review, Trick};
+    use crate::card::Card;
+    use crate::event::Event;
+    use crate::game::{Game, Outcome};
+    use crate::hand::Hand;
+    use crate::strategy::{BackwardsTrick, Strategy};
+    use crate::variant::Variant;
+
+    #[test]
+    fn test_analyze() {
+        let mut game = Game::with_seed(Variant::Standard, 4);
+        game.add_player("Alice");
+        game.add_player("Bob");
+        game.start().unwrap();
+        while game.is_playing() {
+            game.apply(BackwardsTrick.choose(&game.view())).unwrap();
+        }
+
+        let analysis = analyze(Variant::Standard, 4, game.log()).unwrap();
+        assert_eq!(analysis.score, game.score());
+        assert_eq!(analysis.outcome, game.outcome());
+        assert_eq!(analysis.players.iter().map(|player| player.cards_played).sum::<usize>(), 98 - game.score());
+        assert!(analysis.turns.iter().map(|review| review.turn).eq(0..analysis.turns.len()));
+        assert!(analysis.players.iter().all(|player| player.missed_backwards_tricks == 0));
+        if game.outcome() == Some(Outcome::Loss) {
+            assert_eq!(analysis.turns.last().unwrap().left_stuck, Some(game.current_player_index));
+        }
+
+        assert!(analyze(Variant::Standard, 5, game.log()).is_err());
+        assert!(analyze(Variant::Standard, 4, &game.log()[1..]).is_err());
+    }
+
+    #[test]
+    fn test_costly_plays() {
+        let mut game = Game::with_seed(Variant::Standard, 4);
+        game.add_player("Alice");
+        game.add_player("Bob");
+        game.start().unwrap();
+        game.players[0].hand = Hand::from_iter([Card(20), Card(21), Card(30), Card(11), Card(5), Card(8)]);
+        game.players[1].hand = Hand::from_iter([Card(25), Card(3)]);
+        let log = [
+            Event::Discarded { turn: 0, player: 0, card: Card(20), pile: 0 },
+            Event::Discarded { turn: 0, player: 0, card: Card(21), pile: 0 },
+            Event::Discarded { turn: 0, player: 0, card: Card(30), pile: 0 },
+            Event::TurnEnded { turn: 0, player: 0, drawn: Vec::new() },
+        ];
+
+        let analysis = review(game, &log).unwrap();
+        let turn = &analysis.turns[0];
+        assert_eq!(turn.gap_spent, 18 + 8);
+        assert_eq!(turn.missed_backwards_tricks, vec![Trick { card: Card(11), pile: 0 }]);
+        assert_eq!(turn.left_stuck, None);
+
+        let plays = &turn.plays;
+        assert!(!plays[0].optional && !plays[0].is_costly_optional());
+        assert!([Card(3), Card(5), Card(8)].iter().all(|card| plays[0].cut_off.contains(card)));
+        assert!(!plays[0].cut_off.contains(&Card(10)) && !plays[0].cut_off.contains(&Card(20)));
+        assert!(plays[1].cut_off.is_empty());
+        assert!(plays[2].is_costly_optional() && plays[2].cut_off.contains(&Card(25)));
+
+        let alice = &analysis.players[0];
+        assert_eq!((alice.turns, alice.cards_played, alice.missed_backwards_tricks, alice.costly_optional_plays), (1, 3, 1, 1));
+        assert_eq!(analysis.players[1].turns, 0);
+    }
+}
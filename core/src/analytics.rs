@@ -0,0 +1,103 @@
+use serde::{Deserialize, Serialize};
+use crate::card::Card;
+use crate::card_set::CardSet;
+use crate::discard_pile::DiscardPile;
+use crate::strategy::PlayerView;
+
+/// What a player can tell about the cards they can't see, from the piles and their own hand.
+/// Every unseen card is still in the deck or in the hand of another player,
+/// so each of them will have to be played onto one of the piles.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Analytics {
+    /// The cards of the deck played onto the piles.
+    pub gone: CardSet,
+    /// The cards in the hand of the player.
+    pub hand: CardSet,
+    /// The cards of the deck that are neither gone nor in the hand of the player.
+    pub unseen: CardSet,
+    /// The outlook of each pile, in the order of the piles.
+    pub piles: [PileOutlook; 4],
+}
+
+/// How many of the cards still to be played a pile can take.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PileOutlook {
+    /// The number of unseen cards that can still be played onto the pile.
+    pub playable_unseen: usize,
+    /// The share of the cards still to be played, unseen or in the hand, that the pile can take,
+    /// from `0` for a pile nothing fits on to `1` for a pile every card fits on.
+    pub health: f64,
+}
+
+impl Analytics {
+    /// Returns the analytics of the given piles, seen by a player holding the given hand.
+    pub fn new(piles: &[DiscardPile; 4], hand: CardSet) -> Self {
+        let gone = piles.iter().flat_map(|pile| pile.cards()).collect::<CardSet>() & CardSet::deck();
+        let unseen = CardSet::deck() - gone - hand;
+        let piles = piles.each_ref().map(|pile| {
+            let playable = pile.playable_cards();
+            PileOutlook { playable_unseen: (playable & unseen).len(), health: share(playable, unseen | hand) }
+        });
+
+        Self { gone, hand, unseen, piles }
+    }
+
+    /// Returns the chance that an unseen card is one that could be played onto the given pile now,
+    /// but no longer once the given card is played onto it, or `None` if it can't be played.
+    pub fn block_chance(&self, pile: &DiscardPile, card: &Card) -> Option<f64> {
+        let mut played = pile.clone();
+        played.discard_card(card).ok()?;
+        Some(share(pile.playable_cards() - played.playable_cards(), self.unseen))
+    }
+}
+
+impl PlayerView<'_> {
+    /// Returns what the player can tell about the cards they can't see.
+    pub fn analytics(&self) -> Analytics {
+        Analytics::new(self.piles, self.hand.iter().copied().collect())
+    }
+}
+
+/// Returns the share of the cards of `of` that are also in `cards`, `1` if `of` is empty.
+fn share(cards: CardSet, of: CardSet) -> f64 {
+    if of.is_empty() {
+        return 1.0;
+    }
+
+    (cards & of).len() as f64 / of.len() as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::card::Card;
+    use crate::card_set::CardSet;
+    use crate::game::Game;
+    use crate::hand::Hand;
+
+    #[test]
+    fn test_analytics() {
+        let mut game = Game::new();
+        game.add_player("Alice");
+        game.start().unwrap();
+        game.players[0].hand = Hand::from_iter([Card(30), Card(20), Card(50), Card(90), Card(70), Card(11), Card(4), Card(99)]);
+
+        let analytics = game.view().analytics();
+        assert!(analytics.gone.is_empty());
+        assert_eq!(analytics.unseen.len(), 90);
+        assert_eq!(analytics.piles[0].playable_unseen, 90);
+        assert_eq!(analytics.piles[2].health, 1.0);
+
+        game.discard_card(0, &Card(30)).unwrap();
+        game.discard_card(3, &Card(70)).unwrap();
+        let view = game.view();
+        let analytics = view.analytics();
+        assert_eq!(analytics.gone, CardSet::from_iter([Card(30), Card(70)]));
+        assert_eq!(analytics.piles[0].playable_unseen, 65);
+        assert_eq!(analytics.piles[0].health, 69.0 / 96.0);
+        assert_eq!(analytics.piles[3].playable_unseen, 64);
+
+        assert_eq!(analytics.block_chance(&view.piles[0], &Card(20)), Some(0.0));
+        assert_eq!(analytics.block_chance(&view.piles[0], &Card(50)), Some(18.0 / 90.0));
+        assert_eq!(analytics.block_chance(&view.piles[0], &Card(4)), None);
+    }
+}
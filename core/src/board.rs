@@ -1,8 +1,11 @@
+use std::fmt;
+use serde::{Deserialize, Serialize};
 use crate::card::Card;
+use crate::card_set::CardSet;
 use crate::deck::Deck;
 use crate::discard_pile::DiscardPile;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Board {
     /// The deck starts at 98 cards.
     pub deck: Deck,
@@ -14,6 +17,16 @@ pub struct Board {
 impl Board {
     /// Returns new `Board` struct initialized with default values.
     pub fn new() -> Board {
+        Self::default()
+    }
+
+    /// Returns new `Board` struct with a deck shuffled from the given seed.
+    pub fn with_seed(seed: u64) -> Board {
+        Self::with_deck(Deck::new_seeded(seed))
+    }
+
+    /// Returns new `Board` struct with the given deck and fresh discard piles.
+    pub fn with_deck(deck: Deck) -> Board {
         let discard_piles: [DiscardPile; 4] = [
             DiscardPile::new_up(),
             DiscardPile::new_up(),
@@ -21,7 +34,17 @@ impl Board {
             DiscardPile::new_down(),
         ];
 
-        Self { deck: Deck::new_shuffled(), discard_piles }
+        Self { deck, discard_piles }
+    }
+
+    /// Returns new `Board` struct with the given deck and discard piles.
+    pub fn with_piles(deck: Deck, discard_piles: [DiscardPile; 4]) -> Board {
+        Self { deck, discard_piles }
+    }
+
+    /// Returns the four discard piles.
+    pub fn discard_piles(&self) -> &[DiscardPile; 4] {
+        &self.discard_piles
     }
 
     /// Returns true if the given card have any valid moves to make.
@@ -29,9 +52,36 @@ impl Board {
         self.discard_piles.iter().any(|discard_pile| discard_pile.can_play_card(card))
     }
 
+    /// Returns the cards that can be played onto any of the piles.
+    pub fn playable_cards(&self) -> CardSet {
+        self.discard_piles.iter().fold(CardSet::new(), |cards, pile| cards | pile.playable_cards())
+    }
+
+    /// Returns the cards known to be gone from the game, those on the piles.
+    pub fn gone_cards(&self) -> CardSet {
+        self.discard_piles.iter().flat_map(|pile| pile.cards()).collect::<CardSet>() & CardSet::deck()
+    }
+
     /// Adds a card to the given discard pile.
     pub fn discard_card(&mut self, index: usize, card: &Card) -> Result<(), &'static str> {
-        let discard_pile = self.discard_piles.get_mut(index).unwrap();
+        let discard_pile = self.discard_piles.get_mut(index).ok_or("There is no such discard pile")?;
         discard_pile.discard_card(card)
     }
 }
+
+impl fmt::Display for Board {
+    /// Writes the numbered piles and the size of the deck, such as `1: 37^  2: 1^  3: 88v  4: 100v  deck 62`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, pile) in self.discard_piles.iter().enumerate() {
+            write!(f, "{}: {}  ", index + 1, pile)?;
+        }
+
+        write!(f, "deck {}", self.deck.len())
+    }
+}
+
+impl Default for Board {
+    fn default() -> Self {
+        Self::with_deck(Deck::new_shuffled())
+    }
+}
@@ -1,7 +1,17 @@
+use std::fmt;
+use serde::{Deserialize, Serialize};
+
 /// Represents a single card.
-#[derive(Copy, Clone, Hash, PartialEq, Eq, PartialOrd, Debug)]
+#[derive(Copy, Clone, Hash, PartialEq, Eq, PartialOrd, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Card(pub u8);
 
+impl fmt::Display for Card {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
 impl From<u8> for Card {
     fn from(value: u8) -> Self {
         Self(value)
@@ -0,0 +1,267 @@
+use std::fmt;
+use std::ops::{BitAnd, BitOr, Not, Sub};
+use serde::de::{SeqAccess, Visitor};
+use serde::ser::SerializeSeq;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use crate::card::Card;
+
+/// A set of cards, as a mask with one bit per rank.
+/// Cards are iterated over in rank order.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct CardSet(u128);
+
+impl CardSet {
+    /// Returns an empty set.
+    pub const fn new() -> Self {
+        Self(0)
+    }
+
+    /// Returns the set of the cards of a new deck, from 2 to 99.
+    pub const fn deck() -> Self {
+        Self::range(2, 100)
+    }
+
+    /// Returns the set of the ranks from `start` included to `end` excluded, within 0 to 127.
+    pub const fn range(start: u8, end: u8) -> Self {
+        let end = if end > 127 { 127 } else { end };
+        if start >= end {
+            return Self(0);
+        }
+
+        Self((u128::MAX >> (128 - (end - start) as u32)) << start)
+    }
+
+    /// Returns the set as a mask, the bit of a card being `1 << rank`.
+    pub const fn bits(&self) -> u128 {
+        self.0
+    }
+
+    /// Adds a card to the set, returning `false` if it was already in it
+    /// or if its rank is too high for a set to hold.
+    pub fn insert(&mut self, card: Card) -> bool {
+        let absent = bit(&card) != 0 && !self.contains(&card);
+        self.0 |= bit(&card);
+        absent
+    }
+
+    /// Removes a card from the set, returning `false` if it wasn't in it.
+    pub fn remove(&mut self, card: &Card) -> bool {
+        let present = self.contains(card);
+        self.0 &= !bit(card);
+        present
+    }
+
+    /// Returns `true` if the set holds the given card.
+    pub fn contains(&self, card: &Card) -> bool {
+        self.0 & bit(card) != 0
+    }
+
+    /// Returns the number of cards in the set.
+    pub fn len(&self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    /// Returns `true` if the set has no cards.
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns the card of lowest rank, if any.
+    pub fn first(&self) -> Option<Card> {
+        self.iter().next()
+    }
+
+    /// Returns the card at the given index, in rank order.
+    pub fn nth(&self, index: usize) -> Option<Card> {
+        self.iter().nth(index)
+    }
+
+    /// Returns the number of cards ranked below the given one.
+    pub fn rank_of(&self, card: &Card) -> usize {
+        (*self & Self::range(0, card.0)).len()
+    }
+
+    /// Iterates over the cards, in rank order.
+    pub fn iter(&self) -> Iter {
+        Iter(self.0)
+    }
+}
+
+/// Returns the bit of the given card, none for ranks a set can't hold.
+fn bit(card: &Card) -> u128 {
+    1u128.checked_shl(card.0 as u32).unwrap_or_default()
+}
+
+/// An iterator over the cards of a `CardSet`, in rank order.
+#[derive(Debug, Clone)]
+pub struct Iter(u128);
+
+impl Iterator for Iter {
+    type Item = Card;
+
+    fn next(&mut self) -> Option<Card> {
+        if self.0 == 0 {
+            return None;
+        }
+
+        let card = Card(self.0.trailing_zeros() as u8);
+        self.0 &= self.0 - 1;
+        Some(card)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.0.count_ones() as usize;
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for Iter {}
+
+impl IntoIterator for CardSet {
+    type Item = Card;
+    type IntoIter = Iter;
+
+    fn into_iter(self) -> Iter {
+        self.iter()
+    }
+}
+
+impl IntoIterator for &CardSet {
+    type Item = Card;
+    type IntoIter = Iter;
+
+    fn into_iter(self) -> Iter {
+        self.iter()
+    }
+}
+
+impl FromIterator<Card> for CardSet {
+    fn from_iter<T: IntoIterator<Item = Card>>(iter: T) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl<'a> FromIterator<&'a Card> for CardSet {
+    fn from_iter<T: IntoIterator<Item = &'a Card>>(iter: T) -> Self {
+        iter.into_iter().copied().collect()
+    }
+}
+
+impl Extend<Card> for CardSet {
+    fn extend<T: IntoIterator<Item = Card>>(&mut self, iter: T) {
+        for card in iter {
+            self.insert(card);
+        }
+    }
+}
+
+impl BitOr for CardSet {
+    type Output = Self;
+
+    /// Returns the cards in either set.
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl BitAnd for CardSet {
+    type Output = Self;
+
+    /// Returns the cards in both sets.
+    fn bitand(self, rhs: Self) -> Self {
+        Self(self.0 & rhs.0)
+    }
+}
+
+impl Sub for CardSet {
+    type Output = Self;
+
+    /// Returns the cards of this set that aren't in the other one.
+    fn sub(self, rhs: Self) -> Self {
+        Self(self.0 & !rhs.0)
+    }
+}
+
+impl Not for CardSet {
+    type Output = Self;
+
+    /// Returns the cards from 1 to 100 that aren't in this set.
+    fn not(self) -> Self {
+        Self(!self.0) & Self::range(1, 101)
+    }
+}
+
+impl fmt::Debug for CardSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter().map(|card| card.0)).finish()
+    }
+}
+
+impl Serialize for CardSet {
+    /// Serializes the set as a sequence of cards, in rank order.
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.len()))?;
+        for card in self {
+            seq.serialize_element(&card)?;
+        }
+
+        seq.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for CardSet {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct CardsVisitor;
+
+        impl<'de> Visitor<'de> for CardsVisitor {
+            type Value = CardSet;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a sequence of cards")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<CardSet, A::Error> {
+                let mut set = CardSet::new();
+                while let Some(card) = seq.next_element::<Card>()? {
+                    set.insert(card);
+                }
+
+                Ok(set)
+            }
+        }
+
+        deserializer.deserialize_seq(CardsVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::card::Card;
+    use crate::card_set::CardSet;
+
+    #[test]
+    fn test_card_set() {
+        let mut set = [Card(40), Card(2), Card(99)].into_iter().collect::<CardSet>();
+        assert!(set.insert(Card(7)));
+        assert!(!set.insert(Card(7)));
+        assert!(set.remove(&Card(40)));
+        assert!(!set.remove(&Card(40)));
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![Card(2), Card(7), Card(99)]);
+        assert_eq!(set.len(), 3);
+        assert_eq!(set.rank_of(&Card(99)), 2);
+        assert_eq!(set.nth(1), Some(Card(7)));
+
+        let low = CardSet::range(1, 10);
+        assert_eq!((set & low).len(), 2);
+        assert_eq!((set - low).first(), Some(Card(99)));
+        assert_eq!((set | low).len(), 10);
+        assert_eq!((!CardSet::deck()).iter().collect::<Vec<_>>(), vec![Card(1), Card(100)]);
+        assert!(!set.insert(Card(200)) && !set.contains(&Card(200)));
+
+        let json = serde_json::to_string(&set).unwrap();
+        assert_eq!(json, "[2,7,99]");
+        assert_eq!(serde_json::from_str::<CardSet>(&json).unwrap(), set);
+    }
+}
@@ -0,0 +1,143 @@
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+use serde::de::{Error, SeqAccess, Visitor};
+use serde::ser::SerializeSeq;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use crate::card::Card;
+
+/// Up to `N` cards in a fixed order, stored inline so that copying them never allocates.
+/// Cards are pushed onto and popped from the end, which is the top of the stack.
+#[derive(Clone)]
+pub struct CardStack<const N: usize> {
+    cards: [Card; N],
+    len: u8,
+}
+
+impl<const N: usize> CardStack<N> {
+    /// Returns an empty stack.
+    pub const fn new() -> Self {
+        Self { cards: [Card(0); N], len: 0 }
+    }
+
+    /// Puts a card on top of the stack, unless it is full.
+    pub fn push(&mut self, card: Card) -> Result<(), &'static str> {
+        let slot = self.cards.get_mut(self.len as usize).ok_or("Too many cards")?;
+        *slot = card;
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Takes the card on top of the stack, if any.
+    pub fn pop(&mut self) -> Option<Card> {
+        self.len = self.len.checked_sub(1)?;
+        Some(self.cards[self.len as usize])
+    }
+
+    /// Removes the card at the given index, shifting the cards above it down.
+    ///
+    /// Panics if the index is out of bounds.
+    pub fn remove(&mut self, index: usize) -> Card {
+        let card = self[index];
+        self.cards.copy_within(index + 1..self.len as usize, index);
+        self.len -= 1;
+        card
+    }
+}
+
+impl<const N: usize> Default for CardStack<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> Deref for CardStack<N> {
+    type Target = [Card];
+
+    fn deref(&self) -> &[Card] {
+        &self.cards[..self.len as usize]
+    }
+}
+
+impl<const N: usize> DerefMut for CardStack<N> {
+    fn deref_mut(&mut self) -> &mut [Card] {
+        &mut self.cards[..self.len as usize]
+    }
+}
+
+impl<const N: usize> PartialEq for CardStack<N> {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl<const N: usize> Eq for CardStack<N> {}
+
+impl<const N: usize> fmt::Debug for CardStack<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<const N: usize> Serialize for CardStack<N> {
+    /// Serializes the stack as a sequence of cards, the top one last.
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.len()))?;
+        for card in self.iter() {
+            seq.serialize_element(card)?;
+        }
+
+        seq.end()
+    }
+}
+
+impl<'de, const N: usize> Deserialize<'de> for CardStack<N> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct CardsVisitor<const N: usize>(PhantomData<CardStack<N>>);
+
+        impl<'de, const N: usize> Visitor<'de> for CardsVisitor<N> {
+            type Value = CardStack<N>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "a sequence of at most {} cards", N)
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<CardStack<N>, A::Error> {
+                let mut stack = CardStack::new();
+                while let Some(card) = seq.next_element::<Card>()? {
+                    stack.push(card).map_err(|_| A::Error::invalid_length(N + 1, &self))?;
+                }
+
+                Ok(stack)
+            }
+        }
+
+        deserializer.deserialize_seq(CardsVisitor(PhantomData))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::card::Card;
+    use crate::card_stack::CardStack;
+
+    #[test]
+    fn test_card_stack() {
+        let mut stack = CardStack::<3>::new();
+        stack.push(Card(40)).unwrap();
+        stack.push(Card(7)).unwrap();
+        stack.push(Card(93)).unwrap();
+        assert!(stack.push(Card(12)).is_err());
+        assert_eq!(stack.remove(0), Card(40));
+        assert_eq!(*stack, [Card(7), Card(93)]);
+        assert_eq!(stack.pop(), Some(Card(93)));
+        assert_eq!(stack.pop(), Some(Card(7)));
+        assert_eq!(stack.pop(), None);
+
+        stack.push(Card(5)).unwrap();
+        let json = serde_json::to_string(&stack).unwrap();
+        assert_eq!(json, "[5]");
+        assert_eq!(serde_json::from_str::<CardStack<3>>(&json).unwrap(), stack);
+        assert!(serde_json::from_str::<CardStack<3>>("[1,2,3,4]").is_err());
+    }
+}
@@ -1,20 +1,33 @@
-use rand::thread_rng;
+use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
+use rand::{thread_rng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
 use crate::card::Card;
+use crate::card_set::CardSet;
+use crate::card_stack::CardStack;
 use crate::player::Player;
 
 /// The `Deck` represents a deck of zero or more cards.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Deck {
     /// A deck contains zero or more cards.
-    cards: Vec<Card>,
+    cards: CardStack<{ Deck::SIZE }>,
 }
 
 impl Deck {
+    /// The number of cards of a new deck, from 2 to 99.
+    pub const SIZE: usize = 98;
+
+
     pub fn new() -> Self {
         Self::default()
     }
 
+    /// Returns a deck without any card.
+    pub fn empty() -> Self {
+        Self { cards: CardStack::new() }
+    }
+
     /// Generates a new deck of cards, shuffled.
     pub fn new_shuffled() -> Self {
         let mut deck = Self::new();
@@ -27,6 +40,26 @@ impl Deck {
         self.cards.len()
     }
 
+    /// Generates a new deck of cards, always shuffled
+    /// in the same order for the same seed.
+    pub fn new_seeded(seed: u64) -> Self {
+        let mut deck = Self::new();
+        deck.cards.shuffle(&mut StdRng::seed_from_u64(seed));
+        deck
+    }
+
+    /// Returns a deck of the given cards, shuffled with the given generator.
+    /// The cards that aren't part of a new deck are left out.
+    pub fn shuffled_from(cards: CardSet, rng: &mut impl Rng) -> Self {
+        let mut deck = Self::empty();
+        for card in cards & CardSet::deck() {
+            deck.cards.push(card).expect("a deck has room for every card");
+        }
+
+        deck.cards.shuffle(rng);
+        deck
+    }
+
     /// Shuffles the deck.
     pub fn shuffle(&mut self) {
         let mut rng = thread_rng();
@@ -45,6 +78,11 @@ impl Deck {
         }
     }
 
+    /// Returns the cards of this deck, the next one to be drawn last.
+    pub fn cards(&self) -> &[Card] {
+        &self.cards
+    }
+
     /// Returns `true` if this deck is empty.
     pub fn is_empty(&self) -> bool {
         self.cards.is_empty()
@@ -65,8 +103,19 @@ impl Deck {
         dealt_cards
     }
 
+    /// Deals up to `n` cards straight to the `Player`, returning how many were dealt.
+    /// Unlike `deal_to_hand`, it doesn't allocate.
+    pub fn deal(&mut self, player: &mut Player, n: usize) -> usize {
+        let dealt = n.min(self.len());
+        for _ in 0..dealt {
+            player.hand += self.cards.pop().unwrap();
+        }
+
+        dealt
+    }
+
     /// Deal `n` cards to each player's hand.
-    pub fn deal_each(&mut self, players: &mut Vec<Player>, n: usize) {
+    pub fn deal_each(&mut self, players: &mut [Player], n: usize) {
         if self.len() < players.len() * n {
             panic!("Deck has too few cards!");
         }
@@ -79,6 +128,11 @@ impl Deck {
 
 impl Default for Deck {
     fn default() -> Self {
-        Self { cards: (2..99).map(|v| Card(v)).collect() }
+        let mut cards = CardStack::new();
+        for card in CardSet::deck() {
+            cards.push(card).expect("a deck has room for every card");
+        }
+
+        Self { cards }
     }
 }
@@ -1,23 +1,49 @@
+use std::fmt;
+use serde::{Deserialize, Serialize};
 use crate::card::Card;
+use crate::card_set::CardSet;
+use crate::card_stack::CardStack;
 use crate::abs_diff_eq::AbsDiffEq;
+use crate::deck::Deck;
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum Direction {
     Up,
     Down,
 }
 
 /// The `DiscardPile` represents a discard pile of zero or more cards.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DiscardPile {
-    cards: Vec<Card>,
+    /// The starting card and every card of a deck played on top of it.
+    cards: CardStack<{ Deck::SIZE + 1 }>,
     direction: Direction,
 }
 
+impl fmt::Display for Direction {
+    /// Writes `^` for a pile going up and `v` for a pile going down.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Direction::Up => "^",
+            Direction::Down => "v",
+        })
+    }
+}
+
+impl fmt::Display for DiscardPile {
+    /// Writes the top card followed by the direction, such as `37^`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.peek_top_card(), self.direction)
+    }
+}
+
 impl DiscardPile {
     /// Creates a `DiscardPile`, setting the top card to `start_with`.
     pub fn new(start_with: Card, direction: Direction) -> Self {
-        Self { cards: vec![start_with], direction }
+        let mut cards = CardStack::new();
+        cards.push(start_with).expect("an empty pile has room for a card");
+        Self { cards, direction }
     }
 
     /// Returns new `DiscardPile` struct initialized a pile in ascending order.
@@ -36,8 +62,7 @@ impl DiscardPile {
             return Err("Can't play that card");
         }
 
-        self.cards.push(card.clone());
-        Ok(())
+        self.cards.push(*card).map_err(|_| "The pile is full")
     }
 
     /// Returns `true` if the given card can be played.
@@ -45,16 +70,57 @@ impl DiscardPile {
         self.is_card_corresponds_direction(card) || self.is_card_corresponds_rule10(card)
     }
 
+    /// Returns the cards that can be played onto this pile, whether or not they are still in the game.
+    pub fn playable_cards(&self) -> CardSet {
+        let top = self.peek_top_card().0;
+        let (forward, backward) = match self.direction {
+            Direction::Up => (CardSet::range(top + 1, 101), top.checked_sub(10)),
+            Direction::Down => (CardSet::range(1, top), Some(top + 10)),
+        };
+
+        let backward = backward.map_or(CardSet::new(), |card| CardSet::range(card, card + 1));
+        forward | (backward & CardSet::range(1, 101))
+    }
+
+    /// Returns the number of ranks skipped by playing the given card,
+    /// `-10` for a card played backwards by ten, or `None` if it can't be played.
+    pub fn gap(&self, card: &Card) -> Option<i8> {
+        if self.is_card_corresponds_rule10(card) {
+            return Some(-10);
+        }
+
+        if !self.is_card_corresponds_direction(card) {
+            return None;
+        }
+
+        Some(self.peek_top_card().0.abs_diff(card.0) as i8 - 1)
+    }
+
     /// Peeks at the card on top of the pile, leaving it in place.
     pub fn peek_top_card(&self) -> &Card {
         self.cards.last().unwrap()
     }
 
+    /// Returns the cards of this pile, the top one last.
+    pub fn cards(&self) -> &[Card] {
+        &self.cards
+    }
+
+    /// Returns the direction in which cards are played on this pile.
+    pub fn direction(&self) -> Direction {
+        self.direction
+    }
+
     /// Returns the number of cards left in this discard pile.
     pub fn len(&self) -> usize {
         self.cards.len()
     }
 
+    /// Returns `true` if this discard pile has no cards.
+    pub fn is_empty(&self) -> bool {
+        self.cards.is_empty()
+    }
+
     /// Returns `Direction::Up` if the given card is ranked higher than
     /// the top card, `Direction::Down` otherwise.
     fn determine_card_direction(&self, card: &Card) -> Direction {
@@ -82,15 +148,38 @@ mod tests {
     fn test_place_card() {
         let expected_card = &Card(20);
         let mut discard_pile_up = DiscardPile::new_up();
-        discard_pile_up.place_card(expected_card);
+        discard_pile_up.discard_card(expected_card).unwrap();
         assert_eq!(discard_pile_up.peek_top_card(), expected_card);
     }
 
     #[test]
     fn test_rule10() {
         let mut discard_pile_up = DiscardPile::new_up();
-        discard_pile_up.place_card(&Card(20));
-        assert_eq!(discard_pile_up.is_card_corresponds_rule10(&Card(10)), true);
-        assert_eq!(discard_pile_up.is_card_corresponds_rule10(&Card(30)), false);
+        discard_pile_up.discard_card(&Card(20)).unwrap();
+        assert!(discard_pile_up.is_card_corresponds_rule10(&Card(10)));
+        assert!(!discard_pile_up.is_card_corresponds_rule10(&Card(30)));
+    }
+
+    #[test]
+    fn test_gap() {
+        let mut discard_pile_down = DiscardPile::new_down();
+        assert_eq!(discard_pile_down.gap(&Card(99)), Some(0));
+        discard_pile_down.discard_card(&Card(50)).unwrap();
+        assert_eq!(discard_pile_down.gap(&Card(45)), Some(4));
+        assert_eq!(discard_pile_down.gap(&Card(60)), Some(-10));
+        assert_eq!(discard_pile_down.gap(&Card(55)), None);
+    }
+
+    #[test]
+    fn test_playable_cards() {
+        let mut discard_pile_up = DiscardPile::new_up();
+        let mut discard_pile_down = DiscardPile::new_down();
+        discard_pile_up.discard_card(&Card(42)).unwrap();
+        discard_pile_down.discard_card(&Card(95)).unwrap();
+        for pile in [DiscardPile::new_up(), DiscardPile::new_down(), discard_pile_up, discard_pile_down] {
+            let playable = pile.playable_cards();
+            let unplayed = (2..100).map(Card).filter(|card| !pile.cards().contains(card)).collect::<Vec<_>>();
+            assert!(unplayed.iter().all(|card| playable.contains(card) == pile.can_play_card(card)));
+        }
     }
 }
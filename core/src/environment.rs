@@ -0,0 +1,173 @@
+use crate::card::Card;
+use crate::game::Game;
+use crate::strategy::Decision;
+use crate::variant::Variant;
+
+/// The number of values in an observation: the hand as one value per rank from 1 to 100,
+/// the tops of the four piles, the number of cards in the deck and the sizes of the other hands.
+pub const OBSERVATION_SIZE: usize = 100 + 4 + 1 + 4;
+
+/// The number of actions: playing any card onto any of the four piles, or ending the turn.
+pub const ACTIONS: usize = 100 * 4 + 1;
+
+/// An observation of the game from the seat of the current player, with every value between `0` and `1`.
+pub type Observation = [f32; OBSERVATION_SIZE];
+
+/// A game played one action at a time, every seat being played by the same agent,
+/// for training policies against the rules of the game.
+#[derive(Debug, Clone)]
+pub struct Environment {
+    variant: Variant,
+    players: usize,
+    game: Game,
+}
+
+/// What taking an action led to.
+#[derive(Debug, Clone)]
+pub struct Step {
+    pub observation: Observation,
+    /// The number of cards played by the action.
+    pub reward: f32,
+    /// `true` if the game is over.
+    pub done: bool,
+}
+
+impl Environment {
+    /// Returns new `Environment` struct for games with the given rules and number of players,
+    /// from 1 to 5. Its game must be reset before taking actions.
+    pub fn new(variant: Variant, players: usize) -> Result<Self, &'static str> {
+        if !(1..=5).contains(&players) {
+            return Err("A game has from 1 to 5 players");
+        }
+
+        Ok(Self { variant, players, game: Game::with_seed(variant, 0) })
+    }
+
+    /// Starts a new game whose deck is shuffled from the given seed, returning the first observation.
+    pub fn reset(&mut self, seed: u64) -> Observation {
+        self.game = Game::with_seed(self.variant, seed);
+        for seat in 0..self.players {
+            self.game.add_player(format!("Agent {}", seat + 1));
+        }
+
+        self.game.start().expect("a new game can be started");
+        self.observation()
+    }
+
+    /// Takes the given action for the current player. Actions the mask rules out
+    /// are refused, leaving the game as it was.
+    pub fn step(&mut self, action: usize) -> Result<Step, &'static str> {
+        let decision = decode(action).ok_or("There is no such action")?;
+        let score = self.game.score();
+        self.game.apply(decision)?;
+
+        Ok(Step {
+            observation: self.observation(),
+            reward: (score - self.game.score()) as f32,
+            done: !self.game.is_playing(),
+        })
+    }
+
+    /// Returns which actions the current player may take.
+    pub fn action_mask(&self) -> [bool; ACTIONS] {
+        let mut mask = [false; ACTIONS];
+        if !self.game.is_playing() {
+            return mask;
+        }
+
+        for action in self.game.view().plays().filter_map(|play| encode(play.into())) {
+            mask[action] = true;
+        }
+
+        mask[ACTIONS - 1] = self.game.can_end_turn();
+        mask
+    }
+
+    /// Returns what the current player sees of the game.
+    pub fn observation(&self) -> Observation {
+        let mut observation = [0.0; OBSERVATION_SIZE];
+        if self.game.is_created() {
+            return observation;
+        }
+
+        let player = self.game.current_player_index;
+        for card in self.game.players()[player].hand() {
+            observation[card.0 as usize - 1] = 1.0;
+        }
+
+        for (index, pile) in self.game.board().discard_piles().iter().enumerate() {
+            observation[100 + index] = pile.peek_top_card().0 as f32 / 100.0;
+        }
+
+        observation[104] = self.game.board().deck.len() as f32 / 98.0;
+        let max_hand_size = self.game.max_hand_size() as f32;
+        for offset in 1..self.players {
+            let hand = self.game.players()[(player + offset) % self.players].hand.len();
+            observation[104 + offset] = hand as f32 / max_hand_size;
+        }
+
+        observation
+    }
+
+    /// Returns the game being played.
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+}
+
+/// Returns the action of the given decision, or `None` if there is no such card or pile.
+pub fn encode(decision: Decision) -> Option<usize> {
+    match decision {
+        Decision::Play { pile, card } if pile < 4 && (1..=100).contains(&card.0) => Some((card.0 as usize - 1) * 4 + pile),
+        Decision::Play { .. } => None,
+        Decision::EndTurn => Some(ACTIONS - 1),
+    }
+}
+
+/// Returns the decision of the given action, or `None` if there is no such action.
+pub fn decode(action: usize) -> Option<Decision> {
+    match action {
+        action if action < ACTIONS - 1 => Some(Decision::Play { pile: action % 4, card: Card((action / 4 + 1) as u8) }),
+        action if action == ACTIONS - 1 => Some(Decision::EndTurn),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use crate::environment::{decode, encode, Environment, ACTIONS};
+    use crate::variant::Variant;
+
+    #[test]
+    fn test_environment() {
+        assert!(Environment::new(Variant::Standard, 6).is_err());
+        let mut environment = Environment::new(Variant::Standard, 3).unwrap();
+        assert!(environment.step(ACTIONS - 1).is_err());
+
+        let observation = environment.reset(9);
+        assert_eq!(observation[..100].iter().sum::<f32>(), 6.0);
+        assert_eq!(observation[105..], [1.0, 1.0, 0.0, 0.0]);
+
+        let mut rng = StdRng::seed_from_u64(9);
+        let mut total = 0.0;
+        loop {
+            let mask = environment.action_mask();
+            let illegal = mask.iter().position(|allowed| !allowed).unwrap();
+            assert!(environment.step(illegal).is_err());
+            assert!(environment.step(ACTIONS).is_err());
+
+            let legal = (0..ACTIONS).filter(|action| mask[*action]).collect::<Vec<_>>();
+            let step = environment.step(legal[rng.gen_range(0..legal.len())]).unwrap();
+            total += step.reward;
+            if step.done {
+                break;
+            }
+        }
+
+        assert_eq!(total as usize, 98 - environment.game().score());
+        assert!(environment.action_mask().iter().all(|allowed| !allowed));
+        assert!((0..ACTIONS).all(|action| encode(decode(action).unwrap()) == Some(action)));
+    }
+}
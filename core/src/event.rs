@@ -0,0 +1,25 @@
+use serde::{Deserialize, Serialize};
+use crate::card::Card;
+use crate::game::Outcome;
+
+/// Something that happened over the course of a game.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Event {
+    /// The game has started and the opening hands have been dealt
+    /// to the players, named in the order they are seated.
+    Started { players: Vec<String> },
+    /// A player has discarded a card onto the pile at `pile`.
+    Discarded { turn: usize, player: usize, card: Card, pile: usize },
+    /// A player has ended their turn and drawn the given cards.
+    TurnEnded { turn: usize, player: usize, drawn: Vec<Card> },
+    /// A player took the given time to play their turn.
+    TimeUsed { turn: usize, player: usize, millis: u64 },
+    /// A player asked for advice on their turn.
+    AdviceRequested { turn: usize, player: usize },
+    /// A player gave up the game, which is lost.
+    Forfeited { turn: usize, player: usize },
+    /// The game is over.
+    GameOver { outcome: Outcome },
+}
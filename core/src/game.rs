@@ -1,19 +1,28 @@
-use std::fmt::Error;
+use std::time::Duration;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
 use crate::board::Board;
 use crate::card::Card;
+use crate::card_set::CardSet;
 use crate::deck::Deck;
+use crate::event::Event;
 use crate::hand::Hand;
 use crate::player::Player;
+use crate::strategy::{Decision, PlayerView};
+use crate::variant::Variant;
 
 /// A game status representation.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum GameStatus {
     Created,
     Playing,
     GameOver(Outcome),
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
 pub enum Outcome {
     Win,
     Loss,
@@ -21,7 +30,7 @@ pub enum Outcome {
 
 /// The `Game` contains the entirety of the current state
 /// of the game.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Game {
     /// The amount of turns taken by the players over the course of the game.
     pub turn: usize,
@@ -37,21 +46,104 @@ pub struct Game {
     /// Moving through the vector starting from index 0 is equivalent to moving clockwise
     /// around the table of players.
     pub players: Vec<Player>,
+    /// Everything that happened in this game, in order.
+    log: Vec<Event>,
+    /// The rules this game is played with.
+    variant: Variant,
+    /// The seed the deck was shuffled with.
+    seed: u64,
+    /// Whether nothing is written to the log, for the games played out by searches.
+    #[serde(skip)]
+    silent: bool,
 }
 
 impl Game {
     /// Returns new `Game` struct initialized with default values.
     pub fn new() -> Game {
+        Self::default()
+    }
+
+    /// Returns new `Game` struct played with the given rules.
+    pub fn with_variant(variant: Variant) -> Game {
+        Self::with_seed(variant, rand::random())
+    }
+
+    /// Returns new `Game` struct played with the given rules,
+    /// whose deck is shuffled the same way for the same seed.
+    pub fn with_seed(variant: Variant, seed: u64) -> Game {
         Self {
             turn: 0,
             current_player_index: 0,
             actions_taken: 0,
             status: GameStatus::Created,
-            board: Board::new(),
+            board: Board::with_seed(seed),
             players: Vec::with_capacity(5),
+            log: Vec::new(),
+            variant,
+            seed,
+            silent: false,
         }
     }
 
+    /// Returns a game the current player of the given view can't tell apart from the one they see,
+    /// the cards they can't see being dealt at random to the other players and to the deck.
+    /// Nothing is written to the log of the game.
+    pub fn sample(view: &PlayerView, rng: &mut impl Rng) -> Game {
+        let mut game = Self {
+            turn: 0,
+            current_player_index: 0,
+            actions_taken: 0,
+            status: GameStatus::Created,
+            board: Board::with_deck(Deck::empty()),
+            players: Vec::with_capacity(view.hand_sizes.len()),
+            log: Vec::new(),
+            variant: view.variant,
+            seed: 0,
+            silent: true,
+        };
+
+        game.resample(view, rng);
+        game
+    }
+
+    /// Turns this game into a new sample of the given view, like `sample` does,
+    /// reusing its allocations.
+    pub fn resample(&mut self, view: &PlayerView, rng: &mut impl Rng) {
+        let gone = view.piles.iter().flat_map(|pile| pile.cards()).collect::<CardSet>();
+        let unseen = CardSet::deck() - gone - view.hand.iter().collect();
+        let mut deck = Deck::shuffled_from(unseen, rng);
+
+        self.players.resize_with(view.hand_sizes.len(), Player::default);
+        for (seat, (player, size)) in self.players.iter_mut().zip(&view.hand_sizes).enumerate() {
+            if seat == view.player {
+                player.hand = view.hand.iter().copied().collect();
+            } else {
+                player.hand = Hand::new();
+                deck.deal(player, *size);
+            }
+        }
+
+        self.turn = view.turn;
+        self.current_player_index = view.player;
+        self.actions_taken = view.actions_taken;
+        self.status = GameStatus::Playing;
+        self.board = Board::with_piles(deck, view.piles.clone());
+        self.log.clear();
+        self.variant = view.variant;
+        self.seed = 0;
+        self.silent = true;
+    }
+
+    /// Returns the rules this game is played with.
+    pub fn variant(&self) -> Variant {
+        self.variant
+    }
+
+    /// Returns the seed the deck was shuffled with.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
     /// Starts a new game of by shuffling the deck of cards and
     /// deals the players opening hand.
     pub fn start(&mut self) -> Result<(), &'static str> {
@@ -61,20 +153,83 @@ impl Game {
 
         self.status = GameStatus::Playing;
         self.deal_hands();
+        self.record(Event::Started { players: self.players.iter().map(|player| player.name.clone()).collect() });
         Ok(())
     }
 
-    /// Adds a card to the given discard pile.
+    /// Plays a card from the current player's hand onto the given discard pile.
     pub fn discard_card(&mut self, index: usize, card: &Card) -> Result<(), &'static str> {
         if !self.is_playing() {
             return Err("Game is not running");
         }
 
+        if !self.get_current_player().hand.contains(card) {
+            return Err("You don't have that card");
+        }
+
         self.board.discard_card(index, card)?;
+        self.players[self.current_player_index].hand.remove(card);
         self.actions_taken += 1;
+        self.record(Event::Discarded { turn: self.turn, player: self.current_player_index, card: *card, pile: index });
+        self.update_status();
         Ok(())
     }
 
+    /// Returns what the current player can see of the game.
+    pub fn view(&self) -> PlayerView<'_> {
+        PlayerView {
+            player: self.current_player_index,
+            turn: self.turn,
+            variant: self.variant,
+            hand: self.get_current_player().hand(),
+            piles: self.board.discard_piles(),
+            deck: self.board.deck.len(),
+            hand_sizes: self.players.iter().map(|player| player.hand.len()).collect(),
+            actions_taken: self.actions_taken,
+            required_actions: self.get_remaining_actions(),
+        }
+    }
+
+    /// Carries out the decision of the current player.
+    pub fn apply(&mut self, decision: Decision) -> Result<(), &'static str> {
+        match decision {
+            Decision::Play { pile, card } => self.discard_card(pile, &card),
+            Decision::EndTurn => self.finish_turn(),
+        }
+    }
+
+    /// Returns the play of the current player that skips the fewest ranks, as a pile index and a card,
+    /// preferring cards played backwards by ten. Returns `None` if no card can be played.
+    pub fn safest_play(&self) -> Option<(usize, Card)> {
+        let piles = self.board.discard_piles();
+        self.get_current_player().hand().iter()
+            .flat_map(|card| piles.iter().enumerate().filter_map(move |(index, pile)| Some((pile.gap(card)?, index, *card))))
+            .min_by_key(|(gap, _, _)| *gap)
+            .map(|(_, index, card)| (index, card))
+    }
+
+    /// Makes the current player give up, losing the game.
+    pub fn forfeit(&mut self) -> Result<(), &'static str> {
+        if !self.is_playing() {
+            return Err("Game is not running");
+        }
+
+        self.record(Event::Forfeited { turn: self.turn, player: self.current_player_index });
+        self.status = GameStatus::GameOver(Outcome::Loss);
+        self.record(Event::GameOver { outcome: Outcome::Loss });
+        Ok(())
+    }
+
+    /// Records how long the given player took to play the given turn.
+    pub fn record_time(&mut self, turn: usize, player: usize, time: Duration) {
+        self.record(Event::TimeUsed { turn, player, millis: time.as_millis() as u64 });
+    }
+
+    /// Records that the current player asked for advice.
+    pub fn record_advice(&mut self) {
+        self.record(Event::AdviceRequested { turn: self.turn, player: self.current_player_index });
+    }
+
     /// Sets the turn of this game to the next player.
     pub fn next_turn(&mut self) {
         if let Some(next_index) = self.next_player_index() {
@@ -83,11 +238,13 @@ impl Game {
         }
     }
 
-    /// Returns the index of the next player to play.
-    pub fn next_player_index(&mut self) -> Option<usize> {
-        let mut players = self.players.clone();
-        players.rotate_right(self.current_player_index);
-        players.iter().position(|x| !x.hand.is_empty())
+    /// Returns the index of the next player to play, going clockwise
+    /// and skipping the players whose hands are empty.
+    pub fn next_player_index(&self) -> Option<usize> {
+        let len = self.players.len();
+        (1..=len)
+            .map(|offset| (self.current_player_index + offset) % len)
+            .find(|&index| !self.players[index].hand.is_empty())
     }
 
     /// Returns `true` if this `Game` is in play.
@@ -115,17 +272,44 @@ impl Game {
         self.get_current_player().can_play_a_card(&self.board)
     }
 
+    /// Returns `true` if the current player has played the cards the turn requires.
+    pub fn can_end_turn(&self) -> bool {
+        self.is_playing() && self.actions_taken >= self.get_remaining_actions()
+    }
+
     /// Rests the current player's turn
     /// and attempts to find the next player in line.
     pub fn end_turn(&mut self) -> Result<Vec<Card>, &'static str> {
+        let dealt_cards = self.cards_to_draw().collect();
+        self.finish_turn()?;
+        Ok(dealt_cards)
+    }
+
+    /// Ends the turn like `end_turn`, without collecting the cards drawn.
+    fn finish_turn(&mut self) -> Result<(), &'static str> {
+        if !self.is_playing() {
+            return Err("Game is not running");
+        }
+
         if self.actions_taken < self.get_remaining_actions() {
             return Err("An action is required before completing the turn");
         }
 
-        let dealt_cards = self.deal_cards();
+        if !self.silent {
+            self.log.push(Event::TurnEnded { turn: self.turn, player: self.current_player_index, drawn: self.cards_to_draw().collect() });
+        }
+
+        self.deal_cards();
         self.actions_taken = 0;
         self.next_turn();
-        Ok(dealt_cards)
+        self.update_status();
+        Ok(())
+    }
+
+    /// Returns the number of cards that have not been played,
+    /// lower is better and `0` means the game is won.
+    pub fn score(&self) -> usize {
+        self.board.deck.len() + self.players.iter().map(|player| player.hand.len()).sum::<usize>()
     }
 
     /// Returns the current active player.
@@ -133,10 +317,20 @@ impl Game {
         &self.players[self.current_player_index]
     }
 
-    /// Returns `2` if there are still cards left in the draw pile,
-    /// or `1`, if the draw pile is empty.
+    /// Returns the number of cards the variant requires to be played
+    /// if there are still cards left in the draw pile, or `1`, if the draw pile is empty.
     pub fn get_remaining_actions(&self) -> u8 {
-        if self.board.deck.is_empty() { 1 } else { 2 }
+        if self.board.deck.is_empty() { 1 } else { self.variant.required_plays() }
+    }
+
+    /// Returns the board of this game.
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    /// Returns everything that happened in this game, in order.
+    pub fn log(&self) -> &[Event] {
+        &self.log
     }
 
     /// Returns the players of this game in the order they are playing.
@@ -144,9 +338,9 @@ impl Game {
         &self.players
     }
 
-    /// Add a player to the game.
-    pub fn add_player(&mut self) {
-        self.players.push(Player::new());
+    /// Add a player with the given display name to the game.
+    pub fn add_player(&mut self, name: impl Into<String>) {
+        self.players.push(Player::new(name));
     }
 
     /// Deals a hand to all players.
@@ -166,7 +360,7 @@ impl Game {
     }
 
     /// Returns max hand size based on the number of in-game players.
-    fn max_hand_size(&self) -> usize {
+    pub fn max_hand_size(&self) -> usize {
         match self.players.len() {
             1 => 8,
             2 => 7,
@@ -174,9 +368,162 @@ impl Game {
         }
     }
 
-    /// Draws two cards from the top of the deck and
-    /// place them into the current player's hand.
-    fn deal_cards(&mut self) -> Vec<Card> {
-        self.board.deck.deal_to_hand(&mut self.players[self.current_player_index], 2)
+    /// Returns the cards the current player draws when ending the turn, in the order they are drawn.
+    fn cards_to_draw(&self) -> impl Iterator<Item = Card> + '_ {
+        let missing = self.max_hand_size().saturating_sub(self.get_current_player().hand.len());
+        self.board.deck.cards().iter().rev().take(missing).copied()
+    }
+
+    /// Draws cards from the top of the deck until
+    /// the current player's hand is full again.
+    fn deal_cards(&mut self) {
+        let missing = self.max_hand_size().saturating_sub(self.get_current_player().hand.len());
+        self.board.deck.deal(&mut self.players[self.current_player_index], missing);
+    }
+
+    /// Ends the game if all cards have been played, or if the current player
+    /// can't play the cards the turn requires.
+    fn update_status(&mut self) {
+        let outcome = if self.is_won() {
+            Outcome::Win
+        } else if self.actions_taken < self.get_remaining_actions() && !self.can_player_play_card() {
+            Outcome::Loss
+        } else {
+            return;
+        };
+
+        self.status = GameStatus::GameOver(outcome);
+        self.record(Event::GameOver { outcome });
+    }
+
+    /// Writes the given event to the log, unless the game is silent.
+    fn record(&mut self, event: Event) {
+        if !self.silent {
+            self.log.push(event);
+        }
+    }
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self::with_variant(Variant::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::card::Card;
+    use crate::game::{Game, Outcome};
+    use crate::hand::Hand;
+    use crate::variant::Variant;
+
+    fn new_game(players: usize) -> Game {
+        let mut game = Game::new();
+        for index in 0..players {
+            game.add_player(format!("Player {}", index + 1));
+        }
+
+        game.start().unwrap();
+        game
+    }
+
+    /// Draws every card left in the deck of the game.
+    fn empty_deck(game: &mut Game) -> Vec<Card> {
+        let mut cards = Vec::new();
+        while let Ok(card) = game.board.deck.draw() {
+            cards.push(card);
+        }
+
+        cards
+    }
+
+    #[test]
+    fn test_deck() {
+        let mut game = new_game(1);
+        let mut cards = empty_deck(&mut game);
+        cards.extend_from_slice(game.players[0].hand());
+        cards.sort_by_key(|card| card.0);
+        assert_eq!(cards, (2..100).map(Card).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_next_player_index() {
+        let mut game = new_game(3);
+        assert_eq!(game.next_player_index(), Some(1));
+
+        game.players[1].hand = Hand::new();
+        assert_eq!(game.next_player_index(), Some(2));
+
+        game.current_player_index = 2;
+        assert_eq!(game.next_player_index(), Some(0));
+    }
+
+    #[test]
+    fn test_discard_card() {
+        let mut game = new_game(1);
+        let card = game.players[0].hand()[0];
+        assert!(game.discard_card(0, &Card(101)).is_err());
+        assert!(game.discard_card(4, &card).is_err());
+
+        game.discard_card(0, &card).unwrap();
+        assert_eq!(game.players[0].hand.len(), 7);
+        assert_eq!(game.players[0].hand.position(&card), None);
+        assert_eq!(game.board.deck.len(), 90);
+    }
+
+    #[test]
+    fn test_end_turn() {
+        let mut game = new_game(2);
+        let cards = game.players[0].hand()[..2].to_vec();
+        game.discard_card(0, &cards[0]).unwrap();
+        game.discard_card(1, &cards[1]).unwrap();
+
+        let drawn = game.end_turn().unwrap();
+        assert_eq!(drawn.len(), 2);
+        assert_eq!(game.players[0].hand.len(), 7);
+        assert_eq!(game.current_player_index, 1);
+    }
+
+    #[test]
+    fn test_win() {
+        let mut game = new_game(1);
+        empty_deck(&mut game);
+        game.players[0].hand = Hand::from_iter([Card(50)]);
+        game.discard_card(0, &Card(50)).unwrap();
+        assert_eq!(game.outcome(), Some(Outcome::Win));
+    }
+
+    #[test]
+    fn test_seed() {
+        let mut game = Game::with_seed(Variant::Standard, 42);
+        let mut other = Game::with_seed(Variant::Standard, 42);
+        game.add_player("Alice");
+        other.add_player("Alice");
+        game.start().unwrap();
+        other.start().unwrap();
+        assert_eq!(game.players[0].hand(), other.players[0].hand());
+    }
+
+    #[test]
+    fn test_loss() {
+        let mut game = new_game(1);
+        game.players[0].hand = Hand::from_iter([Card(50)]);
+        game.discard_card(0, &Card(50)).unwrap();
+        assert_eq!(game.outcome(), Some(Outcome::Loss));
+        assert!(game.end_turn().is_err());
+    }
+
+    #[test]
+    fn test_safest_play() {
+        let mut game = new_game(1);
+        game.players[0].hand = Hand::from_iter([Card(30), Card(95), Card(4)]);
+        assert_eq!(game.safest_play(), Some((0, Card(4))));
+
+        game.discard_card(0, &Card(30)).unwrap();
+        game.players[0].hand.push_card(Card(20)).unwrap();
+        assert_eq!(game.safest_play(), Some((0, Card(20))));
+
+        game.forfeit().unwrap();
+        assert_eq!(game.outcome(), Some(Outcome::Loss));
     }
 }
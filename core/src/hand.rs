@@ -1,22 +1,49 @@
+use std::fmt;
 use std::ops::AddAssign;
+use serde::{Deserialize, Serialize};
 use crate::card::Card;
+use crate::card_set::CardSet;
+use crate::card_stack::CardStack;
 
 /// A `Hand` is zero or more cards that represents
-/// the cards a person is holding.
-#[derive(Debug, Clone)]
+/// the cards a person is holding, in the order they were dealt.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "Cards", into = "Cards")]
 pub struct Hand {
-    pub cards: Vec<Card>,
+    cards: CardStack<{ Hand::CAPACITY }>,
+    /// The same cards as a set, for fast membership tests and set operations.
+    set: CardSet,
+}
+
+/// How a `Hand` is serialized, the set being rebuilt from the cards.
+#[derive(Serialize, Deserialize)]
+struct Cards {
+    cards: CardStack<{ Hand::CAPACITY }>,
 }
 
 impl Hand {
+    /// The most cards a hand can hold, as many as a single player is dealt.
+    pub const CAPACITY: usize = 8;
+
     /// Create an empty hand.
     pub fn new() -> Self {
         Self::default()
     }
 
-    /// Adds one `Card` to the `Hand`.
-    pub fn push_card(&mut self, card: Card) {
-        self.cards.push(card);
+    /// Adds one `Card` to the `Hand`, unless it is already in it, isn't a card of the deck
+    /// or the hand is full.
+    pub fn push_card(&mut self, card: Card) -> Result<(), &'static str> {
+        if !CardSet::deck().contains(&card) {
+            return Err("There is no such card");
+        }
+
+        if self.set.contains(&card) {
+            return Err("The hand already holds that card");
+        }
+
+        self.cards.push(card).map_err(|_| "The hand is full")?;
+        self.set.insert(card);
+        Ok(())
     }
 
     /// Returns the number of cards.
@@ -29,22 +56,134 @@ impl Hand {
         self.cards.is_empty()
     }
 
+    /// Returns the cards in the order they were dealt.
+    pub fn cards(&self) -> &[Card] {
+        &self.cards
+    }
+
+    /// Returns the cards as a set.
+    pub fn card_set(&self) -> CardSet {
+        self.set
+    }
+
+    /// Returns `true` if the hand holds the given card.
+    pub fn contains(&self, card: &Card) -> bool {
+        self.set.contains(card)
+    }
+
+    /// Returns the index of the given card in this hand.
+    pub fn position(&self, card: &Card) -> Option<usize> {
+        if !self.contains(card) {
+            return None;
+        }
+
+        self.cards.iter().position(|x| x == card)
+    }
+
     /// Play the card at `card_idx`. This removes the card from the hand and
     /// returns it.
     pub fn play(&mut self, card_idx: usize) -> Card {
         assert!(card_idx < self.len());
-        self.cards.remove(card_idx)
+        let card = self.cards.remove(card_idx);
+        self.set.remove(&card);
+        card
+    }
+
+    /// Removes the given card from the hand, returning `false` if it wasn't in it.
+    pub fn remove(&mut self, card: &Card) -> bool {
+        match self.position(card) {
+            Some(position) => {
+                self.play(position);
+                true
+            }
+            None => false,
+        }
     }
 }
 
-impl Default for Hand {
-    fn default() -> Self {
-        Self { cards: Vec::with_capacity(8) }
+impl fmt::Display for Hand {
+    /// Writes the cards in rank order, separated by spaces, or `-` for an empty hand.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return f.write_str("-");
+        }
+
+        let cards = self.set.iter().map(|card| card.to_string()).collect::<Vec<_>>();
+        f.write_str(&cards.join(" "))
     }
 }
 
 impl AddAssign<Card> for Hand {
+    /// Adds one `Card` to the `Hand`.
+    ///
+    /// Panics if the card can't be added, see `push_card`.
     fn add_assign(&mut self, rhs: Card) {
-        self.push_card(rhs);
+        self.push_card(rhs).unwrap();
+    }
+}
+
+impl FromIterator<Card> for Hand {
+    /// Returns a hand of the given cards.
+    ///
+    /// Panics if a card can't be added, see `push_card`.
+    fn from_iter<T: IntoIterator<Item = Card>>(iter: T) -> Self {
+        let mut hand = Self::new();
+        iter.into_iter().for_each(|card| hand += card);
+        hand
+    }
+}
+
+impl TryFrom<Cards> for Hand {
+    type Error = &'static str;
+
+    fn try_from(cards: Cards) -> Result<Self, Self::Error> {
+        let mut hand = Self::new();
+        for card in cards.cards.iter() {
+            hand.push_card(*card)?;
+        }
+
+        Ok(hand)
+    }
+}
+
+impl From<Hand> for Cards {
+    fn from(hand: Hand) -> Self {
+        Self { cards: hand.cards }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::card::Card;
+    use crate::hand::Hand;
+
+    #[test]
+    fn test_deal_order() {
+        let mut hand = Hand::from_iter([Card(40), Card(7), Card(93)]);
+        hand += Card(12);
+        assert_eq!(hand.cards(), &[Card(40), Card(7), Card(93), Card(12)]);
+        assert_eq!(hand.position(&Card(93)), Some(2));
+        assert_eq!(hand.play(1), Card(7));
+        assert!(!hand.contains(&Card(7)) && hand.contains(&Card(12)));
+        assert_eq!(hand.to_string(), "12 40 93");
+
+        let json = serde_json::to_string(&hand).unwrap();
+        assert_eq!(json, r#"{"cards":[40,93,12]}"#);
+        assert_eq!(serde_json::from_str::<Hand>(&json).unwrap(), hand);
+    }
+
+    #[test]
+    fn test_push_card() {
+        let mut hand = Hand::new();
+        assert_eq!(hand.push_card(Card(40)), Ok(()));
+        assert_eq!(hand.push_card(Card(40)), Err("The hand already holds that card"));
+        assert_eq!(hand.push_card(Card(100)), Err("There is no such card"));
+        assert_eq!(hand.push_card(Card(200)), Err("There is no such card"));
+        assert_eq!(hand.cards(), &[Card(40)]);
+
+        (2..9).for_each(|rank| hand += Card(rank));
+        assert_eq!(hand.push_card(Card(9)), Err("The hand is full"));
+        assert!(serde_json::from_str::<Hand>(r#"{"cards":[40,40]}"#).is_err());
+        assert!(serde_json::from_str::<Hand>(r#"{"cards":[150]}"#).is_err());
     }
 }
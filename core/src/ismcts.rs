@@ -0,0 +1,290 @@
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use crate::game::{Game, Outcome};
+use crate::strategy::{BackwardsTrick, Decision, PlayerView, Strategy};
+
+/// Information set Monte Carlo tree search: the decisions of the turn are searched over
+/// many deals of the cards the player can't see, every game being played out with the
+/// `BackwardsTrick` strategy once the turn is over.
+#[derive(Debug)]
+pub struct Ismcts {
+    /// The number of games played out for each decision, shared between the threads.
+    pub iterations: usize,
+    /// The longest time spent on a decision, stopping the search early if reached.
+    pub time_budget: Option<Duration>,
+    /// The number of threads searching at once, each growing a tree of its own.
+    pub threads: usize,
+    /// How much less played decisions are favoured over the best ones.
+    pub exploration: f64,
+    rng: Mutex<StdRng>,
+}
+
+impl Ismcts {
+    /// Returns new `Ismcts` struct with the default budget, single-threaded,
+    /// always deciding the same way for the same seed.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            iterations: 2000,
+            time_budget: None,
+            threads: 1,
+            exploration: 1.0,
+            rng: Mutex::new(StdRng::seed_from_u64(seed)),
+        }
+    }
+}
+
+impl Strategy for Ismcts {
+    fn choose(&self, view: &PlayerView) -> Decision {
+        let decisions = view.decisions().collect::<Vec<_>>();
+        if decisions.len() < 2 {
+            return decisions.first().copied().unwrap_or(Decision::EndTurn);
+        }
+
+        let deadline = self.time_budget.map(|budget| Instant::now() + budget);
+        let threads = self.threads.max(1);
+        let seeds = {
+            let mut rng = self.rng.lock().unwrap();
+            (0..threads).map(|_| rng.gen()).collect::<Vec<u64>>()
+        };
+
+        let trees = thread::scope(|scope| {
+            let handles = seeds.iter()
+                .enumerate()
+                .map(|(index, seed)| {
+                    let iterations = self.iterations / threads + usize::from(index < self.iterations % threads);
+                    scope.spawn(move || {
+                        let mut tree = Tree::new(view, self.exploration);
+                        let mut rng = StdRng::seed_from_u64(*seed);
+                        for _ in 0..iterations {
+                            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                                break;
+                            }
+
+                            tree.iterate(view, &mut rng);
+                        }
+
+                        tree
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            handles.into_iter().map(|handle| handle.join().expect("a search thread panicked")).collect::<Vec<_>>()
+        });
+
+        // The trees of the threads have the same first decisions, in the same order.
+        let stats = (0..decisions.len())
+            .map(|child| trees.iter().map(|tree| tree.nodes[tree.nodes[0].children[child]].stats).fold(Stats::default(), Stats::merge))
+            .collect::<Vec<_>>();
+
+        // The games played out vary a lot with the deal, so the greedy decision is only
+        // given up for one doing better by more than that noise.
+        let greedy = decisions.iter().position(|decision| *decision == BackwardsTrick.choose(view)).unwrap_or_default();
+        let best = (0..decisions.len()).max_by(|a, b| stats[*a].mean().total_cmp(&stats[*b].mean())).unwrap_or(greedy);
+        if stats[best].mean() - stats[greedy].mean() > CONFIDENCE * stats[best].difference_error(&stats[greedy]) {
+            decisions[best]
+        } else {
+            decisions[greedy]
+        }
+    }
+}
+
+/// How many standard errors better than the greedy decision a decision must look to be taken instead.
+const CONFIDENCE: f64 = 2.0;
+
+/// The rewards of the games played through a node.
+#[derive(Debug, Clone, Copy, Default)]
+struct Stats {
+    visits: u32,
+    sum: f64,
+    squares: f64,
+}
+
+impl Stats {
+    fn add(&mut self, reward: f64) {
+        self.visits += 1;
+        self.sum += reward;
+        self.squares += reward * reward;
+    }
+
+    fn merge(self, other: Stats) -> Stats {
+        Stats { visits: self.visits + other.visits, sum: self.sum + other.sum, squares: self.squares + other.squares }
+    }
+
+    fn mean(&self) -> f64 {
+        if self.visits == 0 { 0.0 } else { self.sum / self.visits as f64 }
+    }
+
+    /// Returns the standard error of the difference between the means of two nodes.
+    fn difference_error(&self, other: &Stats) -> f64 {
+        let error = |stats: &Stats| {
+            let visits = stats.visits.max(1) as f64;
+            (stats.squares / visits - stats.mean().powi(2)).max(0.0) / visits
+        };
+
+        (error(self) + error(other)).sqrt()
+    }
+}
+
+/// A decision of the turn, and how well the games taking it went.
+#[derive(Debug)]
+struct Node {
+    decision: Decision,
+    stats: Stats,
+    /// The indices of the nodes of the decisions that follow, empty until first visited.
+    children: Vec<usize>,
+}
+
+/// The decisions of the turn of the searching player, which depend on nothing they can't see.
+/// The turns of the other players are left to the games played out.
+#[derive(Debug)]
+struct Tree {
+    nodes: Vec<Node>,
+    exploration: f64,
+    /// The nodes visited by the current iteration, kept to reuse its allocation.
+    path: Vec<usize>,
+    /// The game played out by the last iteration, kept to reuse its allocations.
+    game: Option<Game>,
+}
+
+impl Tree {
+    fn new(view: &PlayerView, exploration: f64) -> Self {
+        let mut tree = Self { nodes: Vec::new(), exploration, path: Vec::new(), game: None };
+        tree.nodes.push(Node { decision: Decision::EndTurn, stats: Stats::default(), children: Vec::new() });
+        tree.expand(0, view);
+        tree
+    }
+
+    /// Plays one game out from a new deal, down the most promising decisions of the tree.
+    fn iterate(&mut self, view: &PlayerView, rng: &mut StdRng) {
+        let mut game = self.game.take().unwrap_or_default();
+        game.resample(view, rng);
+        let mut path = std::mem::take(&mut self.path);
+        path.push(0);
+        let mut node = 0;
+        while let Some(child) = self.select(node) {
+            let decision = self.nodes[child].decision;
+            game.apply(decision).expect("the tree only holds legal decisions");
+            path.push(child);
+            node = child;
+            if decision == Decision::EndTurn || !game.is_playing() {
+                break;
+            }
+
+            if self.nodes[node].stats.visits == 0 {
+                self.expand(node, &game.view());
+                break;
+            }
+        }
+
+        let reward = play_out(&mut game);
+        for node in path.drain(..) {
+            self.nodes[node].stats.add(reward);
+        }
+
+        self.path = path;
+        self.game = Some(game);
+    }
+
+    /// Returns the child to visit, the unvisited ones first and then by upper confidence bound.
+    fn select(&self, node: usize) -> Option<usize> {
+        let parent = self.nodes[node].stats.visits.max(1) as f64;
+        let bound = |child: &usize| {
+            let stats = &self.nodes[*child].stats;
+            if stats.visits == 0 {
+                return f64::INFINITY;
+            }
+
+            stats.mean() + self.exploration * (parent.ln() / stats.visits as f64).sqrt()
+        };
+
+        self.nodes[node].children.iter().copied().max_by(|a, b| bound(a).total_cmp(&bound(b)))
+    }
+
+    fn expand(&mut self, node: usize, view: &PlayerView) {
+        for decision in view.decisions() {
+            let child = self.nodes.len();
+            self.nodes[node].children.push(child);
+            self.nodes.push(Node { decision, stats: Stats::default(), children: Vec::new() });
+        }
+    }
+}
+
+/// Plays the game out like `BackwardsTrick` would, without building views,
+/// returning `1` for a win and the share of the cards played otherwise.
+fn play_out(game: &mut Game) -> f64 {
+    while game.is_playing() {
+        let decision = match game.safest_play() {
+            Some((pile, card)) if !game.can_end_turn() || game.board().discard_piles()[pile].gap(&card).is_some_and(|gap| gap <= 0) => {
+                Decision::Play { pile, card }
+            }
+            _ => Decision::EndTurn,
+        };
+
+        if game.apply(decision).is_err() {
+            break;
+        }
+    }
+
+    match game.outcome() {
+        Some(Outcome::Win) => 1.0,
+        _ => 1.0 - game.score() as f64 / 98.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use crate::card::Card;
+    use crate::game::Game;
+    use crate::ismcts::Ismcts;
+    use crate::strategy::Strategy;
+    use crate::variant::Variant;
+
+    #[test]
+    fn test_sample() {
+        let mut game = Game::with_seed(Variant::Standard, 3);
+        game.add_player("Alice");
+        game.add_player("Bob");
+        game.add_player("Carol");
+        game.start().unwrap();
+        let card = game.safest_play().unwrap();
+        game.discard_card(card.0, &card.1).unwrap();
+
+        let sample = Game::sample(&game.view(), &mut StdRng::seed_from_u64(1));
+        assert_eq!(sample.get_current_player().hand(), game.get_current_player().hand());
+        assert_eq!(sample.board().deck.len(), game.board().deck.len());
+        assert_eq!(sample.score(), game.score());
+        assert!(sample.log().is_empty());
+
+        let mut cards = sample.players().iter().flat_map(|player| player.hand().to_vec()).collect::<Vec<_>>();
+        cards.extend(sample.board().deck.cards());
+        cards.push(card.1);
+        cards.sort_by_key(|card| card.0);
+        assert_eq!(cards, (2..100).map(Card).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_ismcts() {
+        let mut bot = Ismcts::new(5);
+        bot.iterations = 40;
+        bot.threads = 2;
+        bot.time_budget = Some(Duration::from_secs(1));
+
+        let mut game = Game::with_seed(Variant::Standard, 11);
+        game.add_player("Alice");
+        game.add_player("Bob");
+        game.start().unwrap();
+        while game.is_playing() {
+            game.apply(bot.choose(&game.view())).unwrap();
+        }
+
+        assert!(game.score() < 98);
+        assert!(game.log().len() > 2);
+    }
+}
+
@@ -1,8 +1,20 @@
 pub mod abs_diff_eq;
+pub mod advice;
+pub mod analysis;
+pub mod analytics;
 pub mod board;
 pub mod card;
+pub mod card_set;
+pub mod card_stack;
 pub mod deck;
+pub mod environment;
 pub mod discard_pile;
+pub mod event;
 pub mod game;
 pub mod hand;
+pub mod ismcts;
+pub mod notation;
 pub mod player;
+pub mod solver;
+pub mod strategy;
+pub mod variant;
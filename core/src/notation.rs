@@ -0,0 +1,340 @@
+//! A compact notation for whole games, in the spirit of PGN for chess.
+//!
+//! ```text
+//! [Variant "standard"]
+//! [Seed "42"]
+//! [Player "Alice"]
+//! [Player "Bob"]
+//! [Result "loss"]
+//!
+//! T1 P1: 2^1 9^1 |
+//! T2 P2: 95v3 97v4 |
+//! T3 P1: 19^2 3^1 forfeit
+//! ```
+//!
+//! Each turn line names the turn and the player, counted from 1, then the cards played,
+//! each followed by the direction and the number of its pile, from 1 to 4.
+//! A turn line ends with `|` if the player ended the turn, and with `forfeit` if they gave up.
+//! Anything after a `;` on a turn line is a comment.
+use std::fmt;
+use std::str::FromStr;
+use crate::card::Card;
+use crate::event::Event;
+use crate::game::{Game, Outcome};
+use crate::variant::Variant;
+
+/// A game written down in the notation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Notation {
+    pub variant: Variant,
+    /// The seed the deck was shuffled with.
+    pub seed: u64,
+    /// The names of the players, in the order they are seated.
+    pub players: Vec<String>,
+    /// How the game ended, `None` if it isn't over.
+    pub outcome: Option<Outcome>,
+    pub turns: Vec<TurnLine>,
+}
+
+/// What a player did on one of their turns.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TurnLine {
+    pub turn: usize,
+    pub player: usize,
+    /// The cards played, each with the index of its pile.
+    pub plays: Vec<(usize, Card)>,
+    /// How the turn ended, `None` if the game ended during it or the record stops there.
+    pub ending: Option<Ending>,
+}
+
+/// How a turn ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ending {
+    /// The player ended the turn and drew cards.
+    Ended,
+    /// The player gave up the game.
+    Forfeited,
+}
+
+/// Why a notation couldn't be read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseError {
+    /// The line the error is on, counted from 1.
+    pub line: usize,
+    pub reason: &'static str,
+}
+
+impl Notation {
+    /// Returns the notation of the given game.
+    pub fn from_game(game: &Game) -> Result<Self, &'static str> {
+        Self::from_log(game.variant(), game.seed(), game.log())
+    }
+
+    /// Returns the notation of the game played with the given rules and seed, from its log.
+    pub fn from_log(variant: Variant, seed: u64, log: &[Event]) -> Result<Self, &'static str> {
+        let players = match log.first() {
+            Some(Event::Started { players }) => players.clone(),
+            _ => return Err("The log doesn't start with the game"),
+        };
+
+        let mut notation = Self { variant, seed, players, outcome: None, turns: Vec::new() };
+        for event in &log[1..] {
+            let (turn, player) = match event {
+                Event::Discarded { turn, player, .. } | Event::TurnEnded { turn, player, .. } | Event::Forfeited { turn, player } => (*turn, *player),
+                Event::GameOver { outcome } => {
+                    notation.outcome = Some(*outcome);
+                    continue;
+                }
+                _ => continue,
+            };
+
+            if notation.turns.last().is_none_or(|line| line.turn != turn) {
+                notation.turns.push(TurnLine { turn, player, plays: Vec::new(), ending: None });
+            }
+
+            let line = notation.turns.last_mut().expect("the turn was just written");
+            match event {
+                Event::Discarded { card, pile, .. } => line.plays.push((*pile, *card)),
+                Event::TurnEnded { .. } => line.ending = Some(Ending::Ended),
+                _ => line.ending = Some(Ending::Forfeited),
+            }
+        }
+
+        Ok(notation)
+    }
+
+    /// Plays the game again from its seed, failing if a move isn't legal
+    /// or the game doesn't end the way the notation says.
+    pub fn replay(&self) -> Result<Game, &'static str> {
+        let mut game = Game::with_seed(self.variant, self.seed);
+        for name in &self.players {
+            game.add_player(name.clone());
+        }
+
+        game.start()?;
+        for line in &self.turns {
+            if !game.is_playing() || line.turn != game.turn || line.player != game.current_player_index {
+                return Err("The turns don't match the game");
+            }
+
+            for (pile, card) in &line.plays {
+                game.discard_card(*pile, card)?;
+            }
+
+            match line.ending {
+                Some(Ending::Ended) => {
+                    game.end_turn()?;
+                }
+                Some(Ending::Forfeited) => game.forfeit()?,
+                None => {}
+            }
+        }
+
+        if self.outcome.is_some() && self.outcome != game.outcome() {
+            return Err("The result doesn't match the game");
+        }
+
+        Ok(game)
+    }
+}
+
+/// Returns the sign of the direction cards go on the given pile, the first two going up.
+fn arrow(pile: usize) -> char {
+    if pile < 2 { '^' } else { 'v' }
+}
+
+impl fmt::Display for Notation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "[Variant \"{}\"]", self.variant)?;
+        writeln!(f, "[Seed \"{}\"]", self.seed)?;
+        for name in &self.players {
+            writeln!(f, "[Player \"{}\"]", name.replace('\\', "\\\\").replace('"', "\\\""))?;
+        }
+
+        let result = match self.outcome {
+            Some(Outcome::Win) => "win",
+            Some(Outcome::Loss) => "loss",
+            None => "*",
+        };
+        writeln!(f, "[Result \"{}\"]", result)?;
+
+        for line in &self.turns {
+            writeln!(f)?;
+            write!(f, "T{} P{}:", line.turn + 1, line.player + 1)?;
+            for (pile, card) in &line.plays {
+                write!(f, " {}{}{}", card.0, arrow(*pile), pile + 1)?;
+            }
+
+            match line.ending {
+                Some(Ending::Ended) => write!(f, " |")?,
+                Some(Ending::Forfeited) => write!(f, " forfeit")?,
+                None => {}
+            }
+        }
+
+        writeln!(f)
+    }
+}
+
+impl FromStr for Notation {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut seed = None;
+        let mut notation = Self { variant: Variant::default(), seed: 0, players: Vec::new(), outcome: None, turns: Vec::new() };
+        for (index, line) in s.lines().enumerate() {
+            let error = |reason| ParseError { line: index + 1, reason };
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            if line.starts_with('[') {
+                if !notation.turns.is_empty() {
+                    return Err(error("Tags must come before the turns"));
+                }
+
+                let (name, value) = parse_tag(line).ok_or(error("Malformed tag"))?;
+                match name {
+                    "Variant" => notation.variant = value.parse().map_err(error)?,
+                    "Seed" => seed = Some(value.parse().map_err(|_| error("The seed is not a number"))?),
+                    "Player" => notation.players.push(value),
+                    "Result" => notation.outcome = match value.as_str() {
+                        "win" => Some(Outcome::Win),
+                        "loss" => Some(Outcome::Loss),
+                        "*" => None,
+                        _ => return Err(error("The result is not win, loss or *")),
+                    },
+                    // Tags the notation doesn't know of are kept out of the game, as in PGN.
+                    _ => {}
+                }
+
+                continue;
+            }
+
+            let line = line.split(';').next().unwrap_or_default().trim();
+            if !line.is_empty() {
+                notation.turns.push(parse_turn(line).map_err(error)?);
+            }
+        }
+
+        notation.seed = seed.ok_or(ParseError { line: 1, reason: "The seed is missing" })?;
+        if notation.players.is_empty() {
+            return Err(ParseError { line: 1, reason: "The players are missing" });
+        }
+
+        Ok(notation)
+    }
+}
+
+/// Returns the name and the unescaped value of a tag such as `[Player "Alice"]`.
+fn parse_tag(line: &str) -> Option<(&str, String)> {
+    let (name, value) = line.strip_prefix('[')?.strip_suffix(']')?.split_once(' ')?;
+    let value = value.trim().strip_prefix('"')?.strip_suffix('"')?;
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(char) = chars.next() {
+        match char {
+            '\\' => unescaped.push(chars.next()?),
+            '"' => return None,
+            char => unescaped.push(char),
+        }
+    }
+
+    Some((name, unescaped))
+}
+
+/// Reads a turn line such as `T5 P2: 37^1 41^1 88v3 |`.
+fn parse_turn(line: &str) -> Result<TurnLine, &'static str> {
+    let (head, moves) = line.split_once(':').ok_or("A turn line starts with its turn and player")?;
+    let (turn, player) = head.trim().split_once(' ').ok_or("A turn line starts with its turn and player")?;
+    let number = |value: Option<&str>| value.and_then(|value| value.parse::<usize>().ok()).filter(|number| *number > 0);
+    let turn = number(turn.strip_prefix('T')).ok_or("The turn is not a number from 1")?;
+    let player = number(player.trim().strip_prefix('P')).ok_or("The player is not a number from 1")?;
+
+    let mut line = TurnLine { turn: turn - 1, player: player - 1, plays: Vec::new(), ending: None };
+    for token in moves.split_whitespace() {
+        if line.ending.is_some() {
+            return Err("Nothing may follow the end of the turn");
+        }
+
+        line.ending = match token {
+            "|" => Some(Ending::Ended),
+            "forfeit" => Some(Ending::Forfeited),
+            token => {
+                line.plays.push(parse_play(token)?);
+                None
+            }
+        };
+    }
+
+    Ok(line)
+}
+
+/// Reads a play such as `88v3`.
+fn parse_play(token: &str) -> Result<(usize, Card), &'static str> {
+    let at = token.find(['^', 'v']).ok_or("A play is a card, a direction and a pile")?;
+    let (card, pile) = (&token[..at], &token[at + 1..]);
+    let card = card.parse::<u8>().ok().filter(|card| (1..=100).contains(card)).ok_or("The card is not a number from 1 to 100")?;
+    let pile = pile.parse::<usize>().ok().filter(|pile| (1..=4).contains(pile)).ok_or("The pile is not a number from 1 to 4")? - 1;
+    if !token[at..].starts_with(arrow(pile)) {
+        return Err("The direction doesn't match the pile");
+    }
+
+    Ok((pile, Card(card)))
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.reason)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[cfg(test)]
+mod tests {
+    use crate::card::Card;
+    use crate::game::Game;
+    use crate::notation::{Ending, Notation, ParseError, TurnLine};
+    use crate::strategy::{BackwardsTrick, Strategy};
+    use crate::variant::Variant;
+
+    #[test]
+    fn test_round_trip() {
+        let mut game = Game::with_seed(Variant::Challenge, 21);
+        game.add_player("Alice \"Ace\"");
+        game.add_player("Bob");
+        game.add_player("Carol\\");
+        game.start().unwrap();
+        while game.is_playing() {
+            game.apply(BackwardsTrick.choose(&game.view())).unwrap();
+        }
+
+        let notation = Notation::from_game(&game).unwrap();
+        let text = notation.to_string();
+        assert!(text.starts_with("[Variant \"challenge\"]\n[Seed \"21\"]\n[Player \"Alice \\\"Ace\\\"\"]\n"));
+        assert_eq!(text.parse::<Notation>().unwrap(), notation);
+
+        let replay = notation.replay().unwrap();
+        assert_eq!(replay.log(), game.log());
+        assert_eq!(replay.score(), game.score());
+    }
+
+    #[test]
+    fn test_parse() {
+        let text = "[Seed \"7\"]\n[Event \"Club night\"]\n[Player \"Solo\"]\n\nT1 P1: 37^1 41^2 88v3 | ; nice\nT2 P1: 12v4 forfeit\n";
+        let notation = text.parse::<Notation>().unwrap();
+        assert_eq!(notation.variant, Variant::Standard);
+        assert_eq!(notation.outcome, None);
+        assert_eq!(notation.turns[0], TurnLine { turn: 0, player: 0, plays: vec![(0, Card(37)), (1, Card(41)), (2, Card(88))], ending: Some(Ending::Ended) });
+        assert_eq!(notation.turns[1].ending, Some(Ending::Forfeited));
+
+        let error = |text: &str| text.parse::<Notation>().unwrap_err();
+        assert_eq!(error("[Player \"Solo\"]"), ParseError { line: 1, reason: "The seed is missing" });
+        assert_eq!(error("[Seed \"1\"]\n[Player \"Solo\"]\nT1 P1: 37v1").reason, "The direction doesn't match the pile");
+        assert_eq!(error("[Seed \"1\"]\n[Player \"Solo\"]\nT1 P1: 37^5").line, 3);
+        assert_eq!(error("[Seed \"1\"]\n[Player \"Solo\"]\nT1 P1: 37^1 | 40^1").reason, "Nothing may follow the end of the turn");
+        assert_eq!(error("[Seed \"x\"]").reason, "The seed is not a number");
+    }
+}
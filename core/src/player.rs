@@ -1,25 +1,35 @@
+use serde::{Deserialize, Serialize};
 use crate::board::Board;
 use crate::card::Card;
 use crate::hand::Hand;
 
 /// A player throughout the game.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Player {
     pub hand: Hand,
+    /// The display name of the player.
+    pub name: String,
 }
 
 impl Player {
-    pub fn new() -> Player {
-        Self { hand: Hand::new() }
+    /// Returns new `Player` struct with the given display name and an empty hand.
+    pub fn new(name: impl Into<String>) -> Player {
+        Self { hand: Hand::new(), name: name.into() }
     }
 
     /// Returns true if any of this player's cards have any valid moves to make.
     pub fn can_play_a_card(&self, board: &Board) -> bool {
-        self.hand.cards.iter().any(|card| board.can_play_card(card))
+        !(self.hand.card_set() & board.playable_cards()).is_empty()
     }
 
     /// Gets the hand of this player, suitable for examination.
     pub fn hand(&self) -> &[Card] {
-        &self.hand.cards
+        self.hand.cards()
+    }
+}
+
+impl Default for Player {
+    fn default() -> Self {
+        Self { hand: Hand::new(), name: String::new() }
     }
 }
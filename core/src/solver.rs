@@ -0,0 +1,339 @@
+use std::collections::HashMap;
+use crate::card::Card;
+use crate::card_set::CardSet;
+use crate::discard_pile::Direction;
+use crate::game::Game;
+use crate::strategy::{BackwardsTrick, Decision, Strategy};
+
+/// Finds the best way to play a single-player game, whose deck order is known.
+#[derive(Debug, Clone, Default)]
+pub struct Solver {
+    /// The most positions looked at before settling for the best line found so far, unlimited if `None`.
+    pub node_limit: Option<u64>,
+}
+
+/// The best way found to play a game.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Solution {
+    /// The decisions to take, starting from the position that was solved.
+    pub line: Vec<Decision>,
+    /// The number of cards left unplayed at the end of the line, `0` if the game is won.
+    pub score: usize,
+    /// `true` if no line does better, `false` if the node limit was reached first.
+    pub proven: bool,
+    /// The number of positions looked at.
+    pub nodes: u64,
+}
+
+impl Solution {
+    /// Returns whether the game can be won, or `None` if the node limit was reached before knowing.
+    pub fn winnable(&self) -> Option<bool> {
+        match (self.score, self.proven) {
+            (0, _) => Some(true),
+            (_, true) => Some(false),
+            (_, false) => None,
+        }
+    }
+}
+
+impl Solver {
+    /// Solves the given game from its current position.
+    /// Only running games with a single player can be solved.
+    pub fn solve(&self, game: &Game) -> Result<Solution, &'static str> {
+        if game.players().len() != 1 {
+            return Err("Only single-player games can be solved");
+        }
+        if !game.is_playing() {
+            return Err("Game is not running");
+        }
+
+        // Each line found is improved upon until no better one exists, so that
+        // reaching the node limit still leaves the best line found so far.
+        let mut search = Search::new(game, self.node_limit);
+        let (mut line, mut score) = greedy(game);
+        let mut proven = true;
+        while score > 0 && (search.dead(&search.root) as usize) < score {
+            match search.reach(search.root, score as u8 - 1) {
+                Ok(true) => {
+                    search.line.reverse();
+                    line = std::mem::take(&mut search.line);
+                    score = replay(game, &line);
+                }
+                Ok(false) => break,
+                Err(OutOfNodes) => {
+                    proven = false;
+                    break;
+                }
+            }
+        }
+
+        Ok(Solution { line, score, proven, nodes: search.nodes })
+    }
+}
+
+/// Plays the rest of the game with the `BackwardsTrick` strategy,
+/// returning the decisions taken and the score reached.
+fn greedy(game: &Game) -> (Vec<Decision>, usize) {
+    let mut game = game.clone();
+    let mut line = Vec::new();
+    while game.is_playing() {
+        let decision = BackwardsTrick.choose(&game.view());
+        if game.apply(decision).is_err() {
+            break;
+        }
+
+        line.push(decision);
+    }
+
+    (line, game.score())
+}
+
+/// Returns the score reached by taking the given decisions.
+fn replay(game: &Game, line: &[Decision]) -> usize {
+    let mut game = game.clone();
+    for decision in line {
+        game.apply(*decision).expect("the solver only finds legal lines");
+    }
+
+    game.score()
+}
+
+/// The node limit was reached.
+struct OutOfNodes;
+
+/// A position of a single-player game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Position {
+    hand: CardSet,
+    tops: [u8; 4],
+    /// The number of cards drawn from the deck since the position that is solved.
+    drawn: u8,
+    /// The cards played during the turn, counting only up to the required number.
+    actions: u8,
+}
+
+/// A depth-first search for a line reaching a target score, remembering the positions
+/// that can't reach a score as low as a target.
+struct Search {
+    root: Position,
+    directions: [Direction; 4],
+    /// The cards of the deck, in the order they are drawn.
+    deck: Vec<Card>,
+    /// The cards left in the deck after drawing the given number of them.
+    rest: Vec<CardSet>,
+    hand_size: usize,
+    required: u8,
+    /// The lowest score each position known not to reach it might still reach.
+    memo: HashMap<Position, u8>,
+    nodes: u64,
+    node_limit: Option<u64>,
+    /// The decisions of the line found, last first.
+    line: Vec<Decision>,
+}
+
+impl Search {
+    fn new(game: &Game, node_limit: Option<u64>) -> Self {
+        let piles = game.board().discard_piles();
+        let deck = game.board().deck.cards().iter().rev().copied().collect::<Vec<_>>();
+        let rest = (0..=deck.len()).map(|drawn| deck[drawn..].iter().collect()).collect();
+        let required = game.variant().required_plays();
+        let root = Position {
+            hand: game.get_current_player().hand.card_set(),
+            tops: [0, 1, 2, 3].map(|pile| piles[pile].peek_top_card().0),
+            drawn: 0,
+            actions: game.actions_taken.min(required),
+        };
+
+        Self {
+            root,
+            directions: [0, 1, 2, 3].map(|pile| piles[pile].direction()),
+            deck,
+            rest,
+            hand_size: game.max_hand_size(),
+            required,
+            memo: HashMap::new(),
+            nodes: 0,
+            node_limit,
+            line: Vec::new(),
+        }
+    }
+
+    /// Returns `true` if a line from the given position leaves at most `target` cards unplayed,
+    /// pushing its decisions onto `line`.
+    fn reach(&mut self, position: Position, target: u8) -> Result<bool, OutOfNodes> {
+        self.nodes += 1;
+        if self.node_limit.is_some_and(|limit| self.nodes > limit) {
+            return Err(OutOfNodes);
+        }
+
+        if let Some(score) = self.game_over(&position) {
+            return Ok(score <= target);
+        }
+
+        if self.memo.get(&position).is_some_and(|lowest| *lowest > target) || self.dead(&position) > target {
+            return Ok(false);
+        }
+
+        for decision in self.decisions(&position) {
+            if self.reach(self.apply(&position, decision), target)? {
+                self.line.push(decision);
+                return Ok(true);
+            }
+        }
+
+        self.memo.insert(position, target + 1);
+        Ok(false)
+    }
+
+    /// Returns the legal decisions, the plays skipping the fewest ranks first,
+    /// and ending the turn right after the plays that skip none.
+    fn decisions(&self, position: &Position) -> Vec<Decision> {
+        let mut plays = Vec::with_capacity(32);
+        for card in position.hand {
+            for pile in 0..4 {
+                // Two piles going the same way with the same top card lead to the same positions.
+                let same = (0..pile).any(|other| self.directions[other] == self.directions[pile] && position.tops[other] == position.tops[pile]);
+                if let Some(gap) = self.gap(pile, position.tops[pile], card.0).filter(|_| !same) {
+                    plays.push((gap, Decision::Play { pile, card }));
+                }
+            }
+        }
+
+        if position.actions >= self.required(position) {
+            plays.push((0, Decision::EndTurn));
+        }
+
+        plays.sort_by_key(|(gap, decision)| (*gap, *decision != Decision::EndTurn));
+        plays.into_iter().map(|(_, decision)| decision).collect()
+    }
+
+    fn apply(&self, position: &Position, decision: Decision) -> Position {
+        let mut next = *position;
+        match decision {
+            Decision::Play { pile, card } => {
+                next.hand.remove(&card);
+                next.tops[pile] = card.0;
+                next.actions = (next.actions + 1).min(self.required);
+            }
+            Decision::EndTurn => {
+                let missing = self.hand_size.saturating_sub(next.hand.len());
+                let drawn = missing.min(self.deck.len() - next.drawn as usize);
+                next.hand.extend(self.deck[next.drawn as usize..][..drawn].iter().copied());
+
+                next.drawn += drawn as u8;
+                next.actions = 0;
+            }
+        }
+
+        next
+    }
+
+    /// Returns the score if the game is over in the given position, following the rules of `Game`.
+    fn game_over(&self, position: &Position) -> Option<u8> {
+        let left = self.deck.len() - position.drawn as usize;
+        if left == 0 && position.hand.is_empty() {
+            return Some(0);
+        }
+
+        let stuck = position.hand.iter().all(|card| (0..4).all(|pile| self.gap(pile, position.tops[pile], card.0).is_none()));
+        if position.actions < self.required(position) && stuck {
+            return Some((position.hand.len() + left) as u8);
+        }
+
+        None
+    }
+
+    /// Returns the number of cards that can never be played from the given position, a lower bound of the score.
+    /// A card below the top of a pile going up can only be played after a card exactly ten above it,
+    /// or after a card at most ten below it has been played backwards, and likewise for the piles going down.
+    fn dead(&self, position: &Position) -> u8 {
+        let unplayed = position.hand | self.rest[position.drawn as usize];
+        let alive = |card: u8| {
+            (0..4).any(|pile| {
+                let top = position.tops[pile];
+                match self.directions[pile] {
+                    Direction::Up => card > top || top == card + 10 || unplayed.contains(&Card(card + 10))
+                        || !(unplayed & CardSet::range(card.saturating_sub(10), card)).is_empty(),
+                    Direction::Down => card < top || top + 10 == card || (card >= 10 && unplayed.contains(&Card(card - 10)))
+                        || !(unplayed & CardSet::range(card + 1, card + 11)).is_empty(),
+                }
+            })
+        };
+
+        unplayed.iter().filter(|card| !alive(card.0)).count() as u8
+    }
+
+    /// Returns the number of ranks skipped by playing the given card onto the given pile,
+    /// `-10` for a card played backwards, or `None` if it can't be played.
+    fn gap(&self, pile: usize, top: u8, card: u8) -> Option<i8> {
+        let (forward, backward) = match self.directions[pile] {
+            Direction::Up => (card > top, card + 10 == top),
+            Direction::Down => (card < top, card == top + 10),
+        };
+
+        match (forward, backward) {
+            (_, true) => Some(-10),
+            (true, _) => Some(top.abs_diff(card) as i8 - 1),
+            _ => None,
+        }
+    }
+
+    fn required(&self, position: &Position) -> u8 {
+        if position.drawn as usize == self.deck.len() { 1 } else { self.required }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::Game;
+    use crate::solver::Solver;
+    use crate::strategy::{BackwardsTrick, Strategy};
+    use crate::variant::Variant;
+
+    /// Returns a single-player game played greedily until the deck has few cards left.
+    fn endgame(seed: u64) -> Option<Game> {
+        let mut game = Game::with_seed(Variant::Standard, seed);
+        game.add_player("Alice");
+        game.start().unwrap();
+        while game.is_playing() && game.board().deck.len() > 6 {
+            game.apply(BackwardsTrick.choose(&game.view())).unwrap();
+        }
+
+        game.is_playing().then_some(game)
+    }
+
+    #[test]
+    fn test_solve() {
+        let mut solved = 0;
+        for game in (0..40).filter_map(endgame) {
+            let solution = Solver::default().solve(&game).unwrap();
+            assert!(solution.proven);
+
+            let mut greedy = game.clone();
+            while greedy.is_playing() {
+                greedy.apply(BackwardsTrick.choose(&greedy.view())).unwrap();
+            }
+
+            let mut replayed = game.clone();
+            for decision in &solution.line {
+                replayed.apply(*decision).unwrap();
+            }
+
+            assert!(!replayed.is_playing());
+            assert_eq!(replayed.score(), solution.score);
+            assert!(solution.score <= greedy.score());
+            solved += 1;
+        }
+
+        assert!(solved > 0);
+    }
+
+    #[test]
+    fn test_node_limit() {
+        let game = (0..40).filter_map(endgame).next().unwrap();
+        let solution = Solver { node_limit: Some(1) }.solve(&game).unwrap();
+        assert!(solution.nodes <= 2);
+        assert!(!solution.line.is_empty());
+        assert!(Solver::default().solve(&Game::new()).is_err());
+    }
+}
@@ -0,0 +1,218 @@
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Mutex;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use crate::card::Card;
+use crate::discard_pile::DiscardPile;
+use crate::ismcts::Ismcts;
+use crate::variant::Variant;
+
+/// What a player can see of the game when it is their turn.
+#[derive(Debug, Clone)]
+pub struct PlayerView<'a> {
+    /// The seat of the player.
+    pub player: usize,
+    pub turn: usize,
+    pub variant: Variant,
+    /// The cards in the hand of the player.
+    pub hand: &'a [Card],
+    pub piles: &'a [DiscardPile; 4],
+    /// The number of cards left in the deck.
+    pub deck: usize,
+    /// The number of cards in the hand of each player, in seating order.
+    pub hand_sizes: Vec<usize>,
+    /// The number of cards played during this turn.
+    pub actions_taken: u8,
+    /// The number of cards the turn requires to be played.
+    pub required_actions: u8,
+}
+
+/// A card of the hand that can be played onto a pile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Play {
+    pub pile: usize,
+    pub card: Card,
+    /// The number of ranks skipped, `-10` for a card played backwards by ten.
+    pub gap: i8,
+}
+
+/// What a player does next.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Decision {
+    /// Plays the card onto the pile at `pile`.
+    Play { pile: usize, card: Card },
+    /// Ends the turn.
+    EndTurn,
+}
+
+/// A way of playing the game.
+pub trait Strategy: Send + Sync {
+    /// Chooses what the player does next.
+    fn choose(&self, view: &PlayerView) -> Decision;
+}
+
+impl PlayerView<'_> {
+    /// Returns every play the hand allows.
+    pub fn plays(&self) -> impl Iterator<Item = Play> + '_ {
+        self.hand.iter().flat_map(move |card| {
+            self.piles.iter().enumerate()
+                .filter_map(move |(pile, discard_pile)| Some(Play { pile, card: *card, gap: discard_pile.gap(card)? }))
+        })
+    }
+
+    /// Returns every legal decision, the plays first.
+    pub fn decisions(&self) -> impl Iterator<Item = Decision> + '_ {
+        self.plays().map(Decision::from).chain(self.can_end_turn().then_some(Decision::EndTurn))
+    }
+
+    /// Returns `true` if the cards the turn requires have been played.
+    pub fn can_end_turn(&self) -> bool {
+        self.actions_taken >= self.required_actions
+    }
+}
+
+impl From<Play> for Decision {
+    fn from(play: Play) -> Self {
+        Decision::Play { pile: play.pile, card: play.card }
+    }
+}
+
+/// Plays or ends the turn at random, among the legal decisions.
+#[derive(Debug)]
+pub struct RandomLegal {
+    rng: Mutex<StdRng>,
+}
+
+impl RandomLegal {
+    /// Returns new `RandomLegal` struct, always deciding the same way for the same seed.
+    pub fn new(seed: u64) -> Self {
+        Self { rng: Mutex::new(StdRng::seed_from_u64(seed)) }
+    }
+}
+
+impl Strategy for RandomLegal {
+    fn choose(&self, view: &PlayerView) -> Decision {
+        let plays = view.plays().collect::<Vec<_>>();
+        let choices = plays.len() + usize::from(view.can_end_turn());
+        if choices == 0 {
+            return Decision::EndTurn;
+        }
+
+        let choice = self.rng.lock().unwrap().gen_range(0..choices);
+        plays.get(choice).map_or(Decision::EndTurn, |play| (*play).into())
+    }
+}
+
+/// Plays the cards closest to the top of a pile, and only as many as the turn requires
+/// unless a card follows the top of a pile exactly.
+#[derive(Debug, Default)]
+pub struct SmallestGap;
+
+impl Strategy for SmallestGap {
+    fn choose(&self, view: &PlayerView) -> Decision {
+        let distance = |play: &Play| view.piles[play.pile].peek_top_card().0.abs_diff(play.card.0);
+        match view.plays().min_by_key(distance) {
+            Some(play) if !view.can_end_turn() || distance(&play) == 1 => play.into(),
+            _ => Decision::EndTurn,
+        }
+    }
+}
+
+/// Plays the cards skipping the fewest ranks, and plays every card that can go
+/// backwards by ten, even after the cards the turn requires have been played.
+#[derive(Debug, Default)]
+pub struct BackwardsTrick;
+
+impl Strategy for BackwardsTrick {
+    fn choose(&self, view: &PlayerView) -> Decision {
+        match view.plays().min_by_key(|play| play.gap) {
+            Some(play) if !view.can_end_turn() || play.gap <= 0 => play.into(),
+            _ => Decision::EndTurn,
+        }
+    }
+}
+
+/// The strategies computer players can be given.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BuiltinStrategy {
+    /// See [`RandomLegal`].
+    Random,
+    /// See [`SmallestGap`].
+    SmallestGap,
+    /// See [`BackwardsTrick`].
+    BackwardsTrick,
+    /// See [`Ismcts`].
+    Ismcts,
+}
+
+impl BuiltinStrategy {
+    /// All the built-in strategies.
+    pub const ALL: [BuiltinStrategy; 4] = [
+        BuiltinStrategy::Random,
+        BuiltinStrategy::SmallestGap,
+        BuiltinStrategy::BackwardsTrick,
+        BuiltinStrategy::Ismcts,
+    ];
+
+    /// Returns the name of this strategy.
+    pub fn name(&self) -> &'static str {
+        match self {
+            BuiltinStrategy::Random => "random",
+            BuiltinStrategy::SmallestGap => "smallest_gap",
+            BuiltinStrategy::BackwardsTrick => "backwards_trick",
+            BuiltinStrategy::Ismcts => "ismcts",
+        }
+    }
+
+    /// Returns this strategy, making the same random decisions for the same seed.
+    pub fn build(&self, seed: u64) -> Box<dyn Strategy> {
+        match self {
+            BuiltinStrategy::Random => Box::new(RandomLegal::new(seed)),
+            BuiltinStrategy::SmallestGap => Box::new(SmallestGap),
+            BuiltinStrategy::BackwardsTrick => Box::new(BackwardsTrick),
+            BuiltinStrategy::Ismcts => Box::new(Ismcts::new(seed)),
+        }
+    }
+}
+
+impl fmt::Display for BuiltinStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for BuiltinStrategy {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        BuiltinStrategy::ALL.into_iter().find(|strategy| strategy.name() == s).ok_or("Unknown strategy")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::Game;
+    use crate::strategy::BuiltinStrategy;
+    use crate::variant::Variant;
+
+    #[test]
+    fn test_builtin_strategies() {
+        for strategy in BuiltinStrategy::ALL {
+            let bot = strategy.build(7);
+            let mut game = Game::with_seed(Variant::Standard, 42);
+            game.add_player("Alice");
+            game.add_player("Bob");
+            game.start().unwrap();
+            // The search is too slow to play whole games in debug builds.
+            while game.is_playing() && (strategy != BuiltinStrategy::Ismcts || game.turn < 2) {
+                game.apply(bot.choose(&game.view())).unwrap();
+            }
+
+            assert!(game.score() < 98, "{} didn't play", strategy);
+        }
+    }
+}
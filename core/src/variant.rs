@@ -0,0 +1,51 @@
+use std::fmt;
+use std::str::FromStr;
+use serde::{Deserialize, Serialize};
+
+/// A set of rules the game is played with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum Variant {
+    /// The rules of the original game.
+    #[default]
+    Standard,
+    /// A harder game, where at least three cards must be played each turn
+    /// while the deck has cards.
+    Challenge,
+}
+
+impl Variant {
+    /// All the variants of the game.
+    pub const ALL: [Variant; 2] = [Variant::Standard, Variant::Challenge];
+
+    /// Returns the number of cards that must be played each turn while the deck has cards.
+    pub fn required_plays(&self) -> u8 {
+        match self {
+            Variant::Standard => 2,
+            Variant::Challenge => 3,
+        }
+    }
+
+    /// Returns the name of this variant.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Variant::Standard => "standard",
+            Variant::Challenge => "challenge",
+        }
+    }
+}
+
+impl fmt::Display for Variant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Variant {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Variant::ALL.into_iter().find(|variant| variant.name() == s).ok_or("Unknown variant")
+    }
+}
@@ -0,0 +1,184 @@
+//! The HTTP API, serving the state of the game server to tools
+//! that don't want to open a websocket session.
+use actix::{Addr, MailboxError};
+use actix_web::http::StatusCode;
+use actix_web::web::{get, post, Data, Path, Query, ServiceConfig};
+use actix_web::{HttpResponse, ResponseError};
+use serde::Deserialize;
+use serde_json::json;
+use uuid::Uuid;
+use crate::error::GameError;
+use crate::handlers::prelude::*;
+use crate::server::GameServer;
+
+/// Registers the routes of the API.
+pub fn configure(cfg: &mut ServiceConfig) {
+    cfg.route("/health", get().to(health))
+        .route("/rooms", get().to(list_rooms))
+        .route("/rooms", post().to(create_room))
+        .route("/rooms/{id}", get().to(get_room))
+        .route("/rooms/{id}/history", get().to(room_history))
+        .route("/games", get().to(list_games))
+        .route("/games/{id}", get().to(get_game))
+        .route("/games/{id}/analysis", get().to(analyze_game))
+        .route("/players/{id}/stats", get().to(player_stats))
+        .route("/metrics", get().to(metrics));
+}
+
+/// An error returned by the API.
+#[derive(Debug)]
+pub enum ApiError {
+    /// The game server refused the request.
+    Game(GameError),
+    /// The game server could not be reached.
+    Unavailable(MailboxError),
+}
+
+async fn health(srv: Data<Addr<GameServer>>) -> Result<HttpResponse, ApiError> {
+    let status = srv.send(Health).await?;
+    Ok(HttpResponse::Ok().json(json!({ "status": "ok", "sessions": status.sessions, "rooms": status.rooms })))
+}
+
+async fn list_rooms(srv: Data<Addr<GameServer>>) -> Result<HttpResponse, ApiError> {
+    Ok(HttpResponse::Ok().json(srv.send(ListRooms).await?))
+}
+
+async fn create_room(srv: Data<Addr<GameServer>>) -> Result<HttpResponse, ApiError> {
+    let room_id = srv.send(CreateRoom(None)).await??;
+    Ok(HttpResponse::Created().json(json!({ "room_id": room_id })))
+}
+
+async fn get_room(srv: Data<Addr<GameServer>>, id: Path<Uuid>) -> Result<HttpResponse, ApiError> {
+    Ok(HttpResponse::Ok().json(srv.send(GetRoom(id.into_inner())).await??))
+}
+
+async fn room_history(srv: Data<Addr<GameServer>>, id: Path<Uuid>) -> Result<HttpResponse, ApiError> {
+    Ok(HttpResponse::Ok().json(srv.send(RoomHistory(id.into_inner())).await??))
+}
+
+/// Query parameters of `GET /games`.
+#[derive(Deserialize)]
+struct GamesParams {
+    /// Only lists the games of this player.
+    player: Option<Uuid>,
+}
+
+async fn list_games(srv: Data<Addr<GameServer>>, params: Query<GamesParams>) -> Result<HttpResponse, ApiError> {
+    Ok(HttpResponse::Ok().json(srv.send(ListGames(params.player)).await?))
+}
+
+async fn get_game(srv: Data<Addr<GameServer>>, id: Path<Uuid>) -> Result<HttpResponse, ApiError> {
+    Ok(HttpResponse::Ok().json(srv.send(GetGame(id.into_inner())).await??))
+}
+
+async fn analyze_game(srv: Data<Addr<GameServer>>, id: Path<Uuid>) -> Result<HttpResponse, ApiError> {
+    Ok(HttpResponse::Ok().json(srv.send(AnalyzeGame(id.into_inner())).await??))
+}
+
+async fn player_stats(srv: Data<Addr<GameServer>>, id: Path<Uuid>) -> Result<HttpResponse, ApiError> {
+    Ok(HttpResponse::Ok().json(srv.send(GetPlayerStats(id.into_inner())).await?))
+}
+
+async fn metrics(srv: Data<Addr<GameServer>>) -> Result<HttpResponse, ApiError> {
+    let body = srv.send(GetMetrics).await?;
+    Ok(HttpResponse::Ok().content_type("text/plain; version=0.0.4").body(body))
+}
+
+impl std::fmt::Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ApiError::Game(err) => err.fmt(f),
+            ApiError::Unavailable(err) => err.fmt(f),
+        }
+    }
+}
+
+impl ResponseError for ApiError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            ApiError::Game(GameError::RoomNotFound | GameError::GameNotFound) => StatusCode::NOT_FOUND,
+            ApiError::Game(GameError::NotRoomOwner | GameError::NotInRoom | GameError::InvalidToken | GameError::AdviceDisabled) => {
+                StatusCode::FORBIDDEN
+            }
+            ApiError::Game(GameError::NotYourTurn | GameError::RoomFull | GameError::Rejected(_)) => StatusCode::CONFLICT,
+            ApiError::Game(GameError::InvalidName) => StatusCode::BAD_REQUEST,
+            ApiError::Game(GameError::TooManyRooms | GameError::ShuttingDown) => StatusCode::SERVICE_UNAVAILABLE,
+            ApiError::Unavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        HttpResponse::build(self.status_code()).json(json!({ "error": self.to_string() }))
+    }
+}
+
+impl From<GameError> for ApiError {
+    fn from(err: GameError) -> Self {
+        ApiError::Game(err)
+    }
+}
+
+impl From<MailboxError> for ApiError {
+    fn from(err: MailboxError) -> Self {
+        ApiError::Unavailable(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix::Actor;
+    use actix_web::web::Data;
+    use actix_web::{test, App};
+    use serde_json::Value;
+    use the_game_core::strategy::Decision;
+    use uuid::Uuid;
+    use crate::server::GameServer;
+
+    #[actix_web::test]
+    async fn test_rooms() {
+        let srv = GameServer::default().start();
+        let app = test::init_service(App::new().app_data(Data::new(srv)).configure(super::configure)).await;
+
+        let created: Value = test::call_and_read_body_json(&app, test::TestRequest::post().uri("/rooms").to_request()).await;
+        let room_id = created["room_id"].as_str().unwrap();
+
+        let rooms: Value = test::call_and_read_body_json(&app, test::TestRequest::get().uri("/rooms").to_request()).await;
+        assert_eq!(rooms[0]["id"], room_id);
+        assert_eq!(rooms[0]["status"], "created");
+        assert_eq!(rooms[0]["piles"][0]["top"], 1);
+
+        let uri = format!("/rooms/{}/history", room_id);
+        let history: Value = test::call_and_read_body_json(&app, test::TestRequest::get().uri(&uri).to_request()).await;
+        assert_eq!(history, Value::Array(vec![]));
+
+        let uri = format!("/rooms/{}", uuid::Uuid::new_v4());
+        let res = test::call_service(&app, test::TestRequest::get().uri(&uri).to_request()).await;
+        assert_eq!(res.status(), 404);
+
+        let metrics = test::call_and_read_body(&app, test::TestRequest::get().uri("/metrics").to_request()).await;
+        let metrics = std::str::from_utf8(&metrics).unwrap();
+        assert!(metrics.contains("thegame_rooms{status=\"created\"} 1\n"));
+        assert!(metrics.contains("thegame_messages_total{type=\"create_room\"} 1\n"));
+    }
+
+    #[actix_web::test]
+    async fn test_room_history_hides_drawn_cards() {
+        let mut server = GameServer::default();
+        let room_id = Uuid::new_v4();
+        server.create_room(room_id, Some(Uuid::new_v4())).unwrap();
+        let room = server.rooms.get_mut(&room_id).unwrap();
+        room.join(Uuid::new_v4(), "Bob".to_string()).unwrap();
+        room.start_game().unwrap();
+        while !room.game.can_end_turn() {
+            let (pile, card) = room.game.safest_play().unwrap();
+            room.game.apply(Decision::Play { pile, card }).unwrap();
+        }
+        assert_eq!(room.end_turn().unwrap().len(), 2);
+
+        let app = test::init_service(App::new().app_data(Data::new(server.start())).configure(super::configure)).await;
+        let uri = format!("/rooms/{}/history", room_id);
+        let history: Value = test::call_and_read_body_json(&app, test::TestRequest::get().uri(&uri).to_request()).await;
+        let turn_ended = history.as_array().unwrap().iter().find(|event| event["type"] == "turn_ended").unwrap();
+        assert_eq!(turn_ended["drawn"], Value::Array(vec![]));
+    }
+}
@@ -0,0 +1,17 @@
+//! Writes the JSON Schemas of the protocol into the given directory,
+//! `schema` by default.
+use std::path::PathBuf;
+use std::{env, fs, io};
+use the_game_server::schema;
+
+fn main() -> io::Result<()> {
+    let dir = env::args().nth(1).map(PathBuf::from).unwrap_or_else(|| PathBuf::from("schema"));
+    fs::create_dir_all(&dir)?;
+    for (name, schema) in schema::schemas() {
+        let path = dir.join(format!("{}.json", name));
+        fs::write(&path, serde_json::to_string_pretty(&schema)?)?;
+        println!("{}", path.display());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,60 @@
+//! Computer players, taking their turns on their own.
+use actix::prelude::*;
+use actix_web::rt::task;
+use tracing::{debug, warn};
+use uuid::Uuid;
+use the_game_core::strategy::Decision;
+use crate::clock::TimeoutPolicy;
+use crate::server::GameServer;
+
+impl GameServer {
+    /// Makes the current player of the given room take their turn if they are a bot,
+    /// one decision at a time after thinking for a while.
+    pub fn play_bot(&mut self, room_id: Uuid, ctx: &mut Context<Self>) {
+        let Some(room) = self.rooms.get(&room_id) else {
+            return;
+        };
+
+        let Some(strategy) = room.current_bot() else {
+            return;
+        };
+
+        let turn = room.game.turn;
+        ctx.run_later(self.config.bot_think_delay, move |act, ctx| {
+            let Some(room) = act.rooms.get(&room_id).filter(|room| room.game.is_playing() && room.game.turn == turn) else {
+                return;
+            };
+
+            // Searching strategies take a while, so they decide away from the thread of the server.
+            let game = room.game.clone();
+            let seed = Uuid::new_v4().as_u64_pair().0;
+            let decision = task::spawn_blocking(move || strategy.build(seed).choose(&game.view()));
+            ctx.spawn(decision.into_actor(act).map(move |decision, act, ctx| {
+                if !act.rooms.get(&room_id).is_some_and(|room| room.game.is_playing() && room.game.turn == turn) {
+                    return;
+                }
+
+                let Ok(decision) = decision else {
+                    warn!(room = %room_id, %strategy, "Bot failed to decide");
+                    act.finish_turn(room_id, TimeoutPolicy::PlaySafeMove, ctx);
+                    return;
+                };
+
+                debug!(room = %room_id, %strategy, ?decision, "Bot decided");
+                let result = match decision {
+                    Decision::Play { pile, card } => act.play_card(room_id, pile, card.0),
+                    Decision::EndTurn => act.pass_turn(room_id),
+                };
+
+                match result {
+                    Ok(()) if decision == Decision::EndTurn => act.start_turn(room_id, ctx),
+                    Ok(()) => act.play_bot(room_id, ctx),
+                    Err(err) => {
+                        warn!(room = %room_id, %strategy, ?decision, %err, "Bot made an illegal decision");
+                        act.finish_turn(room_id, TimeoutPolicy::PlaySafeMove, ctx);
+                    }
+                }
+            }));
+        });
+    }
+}
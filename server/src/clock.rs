@@ -0,0 +1,163 @@
+//! The turn clock, limiting how long each player may take to play their turn.
+use std::time::{Duration, SystemTime};
+use actix::prelude::*;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+use uuid::Uuid;
+use crate::messages::MessageToClient;
+use crate::server::GameServer;
+
+/// How many seconds before the end of a turn the player is warned that time is running out.
+const WARNINGS: &[u64] = &[30, 10];
+
+/// How long each turn may last, and what happens once it is over.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct TurnClock {
+    /// How long each turn may last, in seconds.
+    pub time_limit: u64,
+    /// What happens when a player runs out of time.
+    #[serde(default)]
+    pub policy: TimeoutPolicy,
+}
+
+/// What happens when a player runs out of time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeoutPolicy {
+    /// The turn is ended if the required cards were played, otherwise the game is lost.
+    EndTurn,
+    /// The cards skipping the fewest ranks are played until the turn can be ended, then it is ended.
+    #[default]
+    PlaySafeMove,
+    /// The game is lost.
+    Forfeit,
+}
+
+impl GameServer {
+    /// Announces the turn of the current player of the given room, lets them play if they are a bot,
+    /// and runs the turn clock if the room has one.
+    pub fn start_turn(&mut self, room_id: Uuid, ctx: &mut Context<Self>) {
+        let Some(room) = self.rooms.get_mut(&room_id) else {
+            return;
+        };
+
+        if !room.game.is_playing() {
+            return;
+        }
+
+        room.turn_started_at = Some(SystemTime::now());
+        let (turn, player, clock) = (room.game.turn, room.game.current_player_index, room.turn_clock);
+        let name = room.game.players()[player].name.clone();
+        let time_limit = clock.map(|clock| clock.time_limit);
+        self.send_message(&room_id, &MessageToClient::TurnStarted { turn, player, name, time_limit });
+        self.play_bot(room_id, ctx);
+
+        let Some(clock) = clock else {
+            return;
+        };
+
+        // The timers of a turn that is already over do nothing.
+        let time_limit = Duration::from_secs(clock.time_limit);
+        for &seconds_left in WARNINGS.iter().filter(|&&seconds| seconds < clock.time_limit) {
+            ctx.run_later(time_limit - Duration::from_secs(seconds_left), move |act, _| {
+                if act.is_turn(&room_id, turn) {
+                    act.send_message(&room_id, &MessageToClient::TurnClockWarning { player, seconds_left });
+                }
+            });
+        }
+
+        ctx.run_later(time_limit, move |act, ctx| {
+            if act.is_turn(&room_id, turn) {
+                act.time_out(room_id, clock.policy, ctx);
+            }
+        });
+    }
+
+    /// Returns `true` if the game of the given room is still at the given turn.
+    fn is_turn(&self, room_id: &Uuid, turn: usize) -> bool {
+        self.rooms.get(room_id).is_some_and(|room| room.game.is_playing() && room.game.turn == turn)
+    }
+
+    /// Applies the given policy to the current player of the given room, who ran out of time.
+    fn time_out(&mut self, room_id: Uuid, policy: TimeoutPolicy, ctx: &mut Context<Self>) {
+        let player = self.rooms[&room_id].game.current_player_index;
+        info!(room = %room_id, player, ?policy, "Turn timed out");
+        self.send_message(&room_id, &MessageToClient::TurnTimedOut { player, policy });
+        self.finish_turn(room_id, policy, ctx);
+    }
+
+    /// Ends the turn of the current player of the given room as the given policy says.
+    pub fn finish_turn(&mut self, room_id: Uuid, policy: TimeoutPolicy, ctx: &mut Context<Self>) {
+        if policy == TimeoutPolicy::PlaySafeMove {
+            while let Some(room) = self.rooms.get(&room_id).filter(|room| room.game.is_playing()) {
+                if room.game.actions_taken >= room.game.get_remaining_actions() {
+                    break;
+                }
+
+                // A player who can't play has lost the game, which ends the loop.
+                let Some((position, card)) = room.game.safest_play() else {
+                    break;
+                };
+
+                if let Err(err) = self.play_card(room_id, position, card.0) {
+                    warn!(room = %room_id, %err, "Failed to play for the player");
+                    break;
+                }
+            }
+        }
+
+        let Some(room) = self.rooms.get_mut(&room_id).filter(|room| room.game.is_playing()) else {
+            return;
+        };
+
+        // Passing the turn already announces the end of the game, forfeiting doesn't.
+        let result = if policy != TimeoutPolicy::Forfeit && room.game.can_end_turn() {
+            self.pass_turn(room_id)
+        } else {
+            room.forfeit().map_err(Into::into).inspect(|()| {
+                self.touch(room_id);
+                self.announce_game_over(&room_id);
+            })
+        };
+
+        match result {
+            Ok(()) => self.start_turn(room_id, ctx),
+            Err(err) => warn!(room = %room_id, %err, "Failed to end the turn for the player"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix::Context;
+    use the_game_core::event::Event;
+    use the_game_core::game::Game;
+    use the_game_core::strategy::{BackwardsTrick, Decision, Strategy};
+    use the_game_core::variant::Variant;
+    use uuid::Uuid;
+    use crate::clock::TimeoutPolicy;
+    use crate::server::GameServer;
+
+    #[actix_web::test]
+    async fn test_timeout_ends_game() {
+        let mut server = GameServer::default();
+        let mut ctx = Context::new();
+        let (room_id, player) = (Uuid::new_v4(), Uuid::new_v4());
+        server.create_room(room_id, Some(player)).unwrap();
+        let room = server.rooms.get_mut(&room_id).unwrap();
+        room.game = Game::with_seed(Variant::Standard, 2);
+        room.start_game().unwrap();
+
+        // Every turn is ended by the clock, the last one ending the game.
+        while let Some(room) = server.rooms.get(&room_id).filter(|room| room.game.is_playing()) {
+            match BackwardsTrick.choose(&room.game.view()) {
+                Decision::Play { pile, card } => server.play_card(room_id, pile, card.0).unwrap(),
+                Decision::EndTurn => server.finish_turn(room_id, TimeoutPolicy::EndTurn, &mut ctx),
+            }
+        }
+
+        assert!(matches!(server.rooms[&room_id].game.log().iter().rev().nth(1), Some(Event::TurnEnded { .. })));
+        assert_eq!(server.history.games(Some(&player)).count(), 1);
+    }
+}
@@ -0,0 +1,73 @@
+//! Wire formats of the websocket connection.
+use actix_web::web::Bytes;
+use actix_web_actors::ws::CloseReason;
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+/// The encoding of the frames sent over a connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Deserialize, Serialize, JsonSchema)]
+pub enum Codec {
+    /// JSON, sent as text frames.
+    #[default]
+    #[serde(rename = "json")]
+    Json,
+    /// MessagePack, sent as binary frames.
+    #[serde(rename = "msgpack")]
+    MessagePack,
+}
+
+/// An encoded websocket frame.
+#[derive(Debug, Clone)]
+pub enum Frame {
+    Text(String),
+    Binary(Bytes),
+    /// Closes the connection.
+    Close(Option<CloseReason>),
+}
+
+impl Codec {
+    /// Encodes the given value into a frame.
+    ///
+    /// Panics if the value can't be serialized.
+    pub fn encode<T>(&self, value: &T) -> Frame where T: Serialize {
+        match self {
+            Codec::Json => Frame::Text(serde_json::to_string(value).unwrap()),
+            Codec::MessagePack => {
+                let mut buf = Vec::new();
+                let mut serializer = rmp_serde::Serializer::new(&mut buf).with_struct_map().with_human_readable();
+                value.serialize(&mut serializer).unwrap();
+                Frame::Binary(buf.into())
+            }
+        }
+    }
+
+    /// Decodes a value from the payload of a frame.
+    pub fn decode<T>(&self, data: &[u8]) -> Result<T, String> where T: DeserializeOwned {
+        match self {
+            Codec::Json => serde_json::from_slice(data).map_err(|err| err.to_string()),
+            Codec::MessagePack => {
+                let mut deserializer = rmp_serde::Deserializer::new(data).with_human_readable();
+                T::deserialize(&mut deserializer).map_err(|err| err.to_string())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+    use crate::codec::{Codec, Frame};
+
+    #[test]
+    fn test_message_pack_round_trip() {
+        let value = json!({"jsonrpc": "2.0", "method": "create_room", "id": 1});
+        let data = match Codec::MessagePack.encode(&value) {
+            Frame::Binary(data) => data,
+            _ => panic!("MessagePack must be sent as a binary frame"),
+        };
+
+        assert_eq!(Codec::MessagePack.decode::<Value>(&data).unwrap(), value);
+        assert!(Codec::MessagePack.decode::<Value>(b"\xc1").is_err());
+    }
+}
@@ -0,0 +1,412 @@
+//! Settings of the server.
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use clap::{Parser, ValueEnum};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use the_game_core::variant::Variant;
+use crate::messages::MessageFromClient;
+
+/// Command line arguments of the server.
+#[derive(Debug, Default, Parser)]
+#[command(
+    about = "An online multiplayer server for The Game",
+    after_help = "Every setting is taken from, in order of precedence: the command line flag, \
+                  the environment variable, the config file, and finally the default value.",
+)]
+pub struct Args {
+    /// Path to a TOML config file.
+    #[arg(long, short, env = "THEGAME_CONFIG")]
+    pub config: Option<PathBuf>,
+    /// The address to listen on [default: 127.0.0.1].
+    #[arg(long, env = "THEGAME_HOST")]
+    pub host: Option<String>,
+    /// The port to listen on [default: 8080].
+    #[arg(long, short, env = "THEGAME_PORT")]
+    pub port: Option<u16>,
+    /// The number of HTTP workers [default: the number of CPU cores].
+    #[arg(long, env = "THEGAME_WORKERS")]
+    pub workers: Option<usize>,
+    /// How often heartbeat pings are sent, in seconds [default: 5].
+    #[arg(long, env = "THEGAME_HEARTBEAT_INTERVAL")]
+    pub heartbeat_interval: Option<u64>,
+    /// How long before lack of client response causes a timeout, in seconds [default: 30].
+    #[arg(long, env = "THEGAME_CLIENT_TIMEOUT")]
+    pub client_timeout: Option<u64>,
+    /// The maximum number of rooms [default: 1000].
+    #[arg(long, env = "THEGAME_MAX_ROOMS")]
+    pub max_rooms: Option<usize>,
+    /// The maximum number of players in a room, from 1 to 5 [default: 5].
+    #[arg(long, env = "THEGAME_MAX_PLAYERS")]
+    pub max_players: Option<usize>,
+    /// How long a disconnected player keeps their seat, in seconds [default: 60].
+    /// Seats in running games are kept until the room is closed.
+    #[arg(long, env = "THEGAME_RECONNECT_GRACE")]
+    pub reconnect_grace: Option<u64>,
+    /// The rules new rooms are played with, `standard` or `challenge` [default: standard].
+    #[arg(long, env = "THEGAME_VARIANT")]
+    pub variant: Option<Variant>,
+    /// Which logs to write, as a level or a list of `target=level` directives [default: info].
+    #[arg(long, env = "THEGAME_LOG_LEVEL")]
+    pub log_level: Option<String>,
+    /// The format of the logs [default: plain].
+    #[arg(long, env = "THEGAME_LOG_FORMAT")]
+    pub log_format: Option<LogFormat>,
+    /// The largest websocket frame accepted, in bytes [default: 65536].
+    #[arg(long, env = "THEGAME_MAX_FRAME_SIZE")]
+    pub max_frame_size: Option<usize>,
+    /// How many rate limit violations a session may commit before it is disconnected [default: 20].
+    #[arg(long, env = "THEGAME_MAX_VIOLATIONS")]
+    pub max_violations: Option<u32>,
+    /// The directory rooms are saved to, rooms are kept in memory only if unset.
+    #[arg(long, env = "THEGAME_DATA_DIR")]
+    pub data_dir: Option<PathBuf>,
+    /// How often changed rooms are saved, in seconds, `0` saves them on every change [default: 0].
+    #[arg(long, env = "THEGAME_SNAPSHOT_INTERVAL")]
+    pub snapshot_interval: Option<u64>,
+    /// How long sessions are warned before the server closes them when shutting down, in seconds [default: 5].
+    #[arg(long, env = "THEGAME_SHUTDOWN_COUNTDOWN")]
+    pub shutdown_countdown: Option<u64>,
+    /// The longest a shutdown may take before the server stops abruptly, in seconds [default: 15].
+    #[arg(long, env = "THEGAME_SHUTDOWN_TIMEOUT")]
+    pub shutdown_timeout: Option<u64>,
+    /// How long a room may go without activity before it is closed, in seconds [default: 1800].
+    #[arg(long, env = "THEGAME_ROOM_IDLE_TIMEOUT")]
+    pub room_idle_timeout: Option<u64>,
+    /// How often empty and idle rooms are looked for, in seconds [default: 60].
+    #[arg(long, env = "THEGAME_SWEEP_INTERVAL")]
+    pub sweep_interval: Option<u64>,
+    /// How long bots think before each decision, in milliseconds [default: 800].
+    #[arg(long, env = "THEGAME_BOT_THINK_DELAY")]
+    pub bot_think_delay: Option<u64>,
+    /// The secret player tokens are signed with [default: random, so tokens don't survive a restart].
+    #[arg(long, env = "THEGAME_SECRET", hide_env_values = true)]
+    pub secret: Option<String>,
+}
+
+/// Settings of the server.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    /// The address to listen on.
+    pub host: String,
+    /// The port to listen on.
+    pub port: u16,
+    /// The number of HTTP workers, the number of CPU cores if `None`.
+    pub workers: Option<usize>,
+    /// How often heartbeat pings are sent.
+    #[serde(with = "seconds")]
+    pub heartbeat_interval: Duration,
+    /// How long before lack of client response causes a timeout.
+    #[serde(with = "seconds")]
+    pub client_timeout: Duration,
+    /// The maximum number of rooms.
+    pub max_rooms: usize,
+    /// The maximum number of players in a room.
+    pub max_players: usize,
+    /// How long a disconnected player keeps their seat, unless their game is running.
+    #[serde(with = "seconds")]
+    pub reconnect_grace: Duration,
+    /// The rules new rooms are played with.
+    pub variant: Variant,
+    /// Which logs to write, as a level or a list of `target=level` directives.
+    pub log_level: String,
+    /// The format of the logs.
+    pub log_format: LogFormat,
+    /// The largest websocket frame accepted, in bytes.
+    pub max_frame_size: usize,
+    /// How many rate limit violations a session may commit before it is disconnected.
+    pub max_violations: u32,
+    /// The rate limit of each method, the `default` one applies to the methods not listed.
+    pub rate_limits: BTreeMap<String, RateLimit>,
+    /// The directory rooms are saved to, rooms are kept in memory only if `None`.
+    pub data_dir: Option<PathBuf>,
+    /// How often changed rooms are saved, zero saves them on every change.
+    #[serde(with = "seconds")]
+    pub snapshot_interval: Duration,
+    /// How long sessions are warned before the server closes them when shutting down.
+    #[serde(with = "seconds")]
+    pub shutdown_countdown: Duration,
+    /// The longest a shutdown may take before the server stops abruptly.
+    #[serde(with = "seconds")]
+    pub shutdown_timeout: Duration,
+    /// How long a room may go without activity before it is closed.
+    #[serde(with = "seconds")]
+    pub room_idle_timeout: Duration,
+    /// How often empty and idle rooms are looked for.
+    #[serde(with = "seconds")]
+    pub sweep_interval: Duration,
+    /// How long bots think before each decision.
+    #[serde(with = "milliseconds")]
+    pub bot_think_delay: Duration,
+    /// The secret player tokens are signed with.
+    pub secret: String,
+}
+
+/// A token bucket limiting how often a session may call a method.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RateLimit {
+    /// How many calls may be made in a row.
+    pub burst: u32,
+    /// How many calls are allowed per second in the long run.
+    pub per_second: f64,
+}
+
+/// The format of the logs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum LogFormat {
+    /// Human readable lines.
+    #[default]
+    Plain,
+    /// One JSON object per line.
+    Json,
+}
+
+/// A reason why the settings could not be loaded.
+#[derive(Debug)]
+pub enum ConfigError {
+    /// The config file could not be read.
+    Io(PathBuf, std::io::Error),
+    /// The config file is not valid TOML.
+    Parse(PathBuf, toml::de::Error),
+    /// A setting has an invalid value.
+    Invalid(&'static str),
+}
+
+impl ServerConfig {
+    /// Loads the settings from the command line arguments, the environment
+    /// and the config file.
+    pub fn load(args: Args) -> Result<Self, ConfigError> {
+        let mut config = match &args.config {
+            Some(path) => Self::from_file(path)?,
+            None => Self::default(),
+        };
+
+        config.merge(args);
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Reads the settings from a TOML file.
+    pub fn from_file(path: &Path) -> Result<Self, ConfigError> {
+        let content = std::fs::read_to_string(path).map_err(|err| ConfigError::Io(path.to_path_buf(), err))?;
+        toml::from_str(&content).map_err(|err| ConfigError::Parse(path.to_path_buf(), err))
+    }
+
+    /// Overrides the settings with the ones given as arguments.
+    fn merge(&mut self, args: Args) {
+        if let Some(host) = args.host {
+            self.host = host;
+        }
+        if let Some(port) = args.port {
+            self.port = port;
+        }
+        if let Some(workers) = args.workers {
+            self.workers = Some(workers);
+        }
+        if let Some(secs) = args.heartbeat_interval {
+            self.heartbeat_interval = Duration::from_secs(secs);
+        }
+        if let Some(secs) = args.client_timeout {
+            self.client_timeout = Duration::from_secs(secs);
+        }
+        if let Some(max_rooms) = args.max_rooms {
+            self.max_rooms = max_rooms;
+        }
+        if let Some(max_players) = args.max_players {
+            self.max_players = max_players;
+        }
+        if let Some(secs) = args.reconnect_grace {
+            self.reconnect_grace = Duration::from_secs(secs);
+        }
+        if let Some(variant) = args.variant {
+            self.variant = variant;
+        }
+        if let Some(log_level) = args.log_level {
+            self.log_level = log_level;
+        }
+        if let Some(log_format) = args.log_format {
+            self.log_format = log_format;
+        }
+        if let Some(max_frame_size) = args.max_frame_size {
+            self.max_frame_size = max_frame_size;
+        }
+        if let Some(max_violations) = args.max_violations {
+            self.max_violations = max_violations;
+        }
+        if let Some(data_dir) = args.data_dir {
+            self.data_dir = Some(data_dir);
+        }
+        if let Some(secs) = args.snapshot_interval {
+            self.snapshot_interval = Duration::from_secs(secs);
+        }
+        if let Some(secs) = args.shutdown_countdown {
+            self.shutdown_countdown = Duration::from_secs(secs);
+        }
+        if let Some(secs) = args.shutdown_timeout {
+            self.shutdown_timeout = Duration::from_secs(secs);
+        }
+        if let Some(secs) = args.room_idle_timeout {
+            self.room_idle_timeout = Duration::from_secs(secs);
+        }
+        if let Some(secs) = args.sweep_interval {
+            self.sweep_interval = Duration::from_secs(secs);
+        }
+        if let Some(millis) = args.bot_think_delay {
+            self.bot_think_delay = Duration::from_millis(millis);
+        }
+        if let Some(secret) = args.secret {
+            self.secret = secret;
+        }
+    }
+
+    /// Checks that the settings make sense together.
+    fn validate(&self) -> Result<(), ConfigError> {
+        if !(1..=5).contains(&self.max_players) {
+            return Err(ConfigError::Invalid("max_players must be between 1 and 5"));
+        }
+        if self.workers == Some(0) {
+            return Err(ConfigError::Invalid("workers must be at least 1"));
+        }
+        if self.heartbeat_interval.is_zero() || self.heartbeat_interval >= self.client_timeout {
+            return Err(ConfigError::Invalid("heartbeat_interval must be positive and shorter than client_timeout"));
+        }
+        if self.shutdown_countdown >= self.shutdown_timeout {
+            return Err(ConfigError::Invalid("shutdown_countdown must be shorter than shutdown_timeout"));
+        }
+        if self.sweep_interval.is_zero() {
+            return Err(ConfigError::Invalid("sweep_interval must be positive"));
+        }
+        if self.secret.len() < 16 {
+            return Err(ConfigError::Invalid("secret must be at least 16 characters long"));
+        }
+        if self.max_frame_size == 0 {
+            return Err(ConfigError::Invalid("max_frame_size must be at least 1"));
+        }
+        for (method, limit) in &self.rate_limits {
+            if method != "default" && !MessageFromClient::METHODS.contains(&method.as_str()) {
+                return Err(ConfigError::Invalid("rate_limits must be named after a method or `default`"));
+            }
+            if limit.burst == 0 || limit.per_second <= 0.0 {
+                return Err(ConfigError::Invalid("rate_limits must have a positive burst and per_second"));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the rate limit of the given method, if it is limited.
+    pub fn rate_limit(&self, method: &str) -> Option<&RateLimit> {
+        self.rate_limits.get(method).or_else(|| self.rate_limits.get("default"))
+    }
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            host: "127.0.0.1".to_string(),
+            port: 8080,
+            workers: None,
+            heartbeat_interval: Duration::from_secs(5),
+            client_timeout: Duration::from_secs(30),
+            max_rooms: 1000,
+            max_players: 5,
+            reconnect_grace: Duration::from_secs(60),
+            variant: Variant::Standard,
+            log_level: "info".to_string(),
+            log_format: LogFormat::Plain,
+            max_frame_size: 64 * 1024,
+            max_violations: 20,
+            rate_limits: BTreeMap::from([
+                ("default".to_string(), RateLimit { burst: 20, per_second: 10.0 }),
+                ("create_room".to_string(), RateLimit { burst: 3, per_second: 0.2 }),
+            ]),
+            data_dir: None,
+            snapshot_interval: Duration::ZERO,
+            shutdown_countdown: Duration::from_secs(5),
+            shutdown_timeout: Duration::from_secs(15),
+            room_idle_timeout: Duration::from_secs(30 * 60),
+            sweep_interval: Duration::from_secs(60),
+            bot_think_delay: Duration::from_millis(800),
+            secret: Uuid::new_v4().simple().to_string(),
+        }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(path, err) => write!(f, "Failed to read {}: {}", path.display(), err),
+            ConfigError::Parse(path, err) => write!(f, "Failed to parse {}: {}", path.display(), err),
+            ConfigError::Invalid(reason) => f.write_str(reason),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// (De)serializes a `Duration` as a number of seconds.
+mod seconds {
+    use std::time::Duration;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        serializer.serialize_u64(duration.as_secs())
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Duration, D::Error> where D: Deserializer<'de> {
+        u64::deserialize(deserializer).map(Duration::from_secs)
+    }
+}
+
+/// (De)serializes a `Duration` as a number of milliseconds.
+mod milliseconds {
+    use std::time::Duration;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        serializer.serialize_u64(duration.as_millis() as u64)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Duration, D::Error> where D: Deserializer<'de> {
+        u64::deserialize(deserializer).map(Duration::from_millis)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use the_game_core::variant::Variant;
+    use crate::config::{Args, ServerConfig};
+
+    #[test]
+    fn test_precedence() {
+        let mut config: ServerConfig = toml::from_str("port = 9000\nmax_rooms = 10\nvariant = \"challenge\"").unwrap();
+        assert_eq!(config.host, "127.0.0.1");
+        assert_eq!(config.variant, Variant::Challenge);
+
+        config.merge(Args { port: Some(9001), reconnect_grace: Some(5), ..Args::default() });
+        assert_eq!(config.port, 9001);
+        assert_eq!(config.max_rooms, 10);
+        assert_eq!(config.reconnect_grace, Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(ServerConfig::default().validate().is_ok());
+        assert!(ServerConfig { max_players: 6, ..ServerConfig::default() }.validate().is_err());
+        assert!(toml::from_str::<ServerConfig>("prot = 9000").is_err());
+
+        let config: ServerConfig = toml::from_str("[rate_limits]\nend_turn = { burst = 1, per_second = 0.5 }").unwrap();
+        assert!(config.validate().is_ok());
+        assert_eq!(config.rate_limit("end_turn").unwrap().burst, 1);
+        assert_eq!(config.rate_limit("join_room"), None);
+
+        let config: ServerConfig = toml::from_str("[rate_limits]\nend_game = { burst = 1, per_second = 0.5 }").unwrap();
+        assert!(config.validate().is_err());
+    }
+}
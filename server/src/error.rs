@@ -0,0 +1,75 @@
+use std::fmt;
+
+/// A reason why the game server refused to carry out a request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameError {
+    /// There is no room with the given id.
+    RoomNotFound,
+    /// Only the room owner may perform this action.
+    NotRoomOwner,
+    /// The session is not a member of the room.
+    NotInRoom,
+    /// The action may only be performed by the player who has the turn.
+    NotYourTurn,
+    /// The server can't host any more rooms.
+    TooManyRooms,
+    /// The room has no free seats.
+    RoomFull,
+    /// The reconnect token does not belong to any session.
+    InvalidToken,
+    /// There is no finished game with the given id.
+    GameNotFound,
+    /// The display name is empty, too long or not printable.
+    InvalidName,
+    /// The server is shutting down and doesn't take new rooms.
+    ShuttingDown,
+    /// The room owner hasn't turned advice on.
+    AdviceDisabled,
+    /// The game rules do not allow this action.
+    Rejected(&'static str),
+}
+
+impl GameError {
+    /// A short name of the error, used as a metrics label.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            GameError::RoomNotFound => "room_not_found",
+            GameError::NotRoomOwner => "not_room_owner",
+            GameError::NotInRoom => "not_in_room",
+            GameError::NotYourTurn => "not_your_turn",
+            GameError::TooManyRooms => "too_many_rooms",
+            GameError::RoomFull => "room_full",
+            GameError::InvalidToken => "invalid_token",
+            GameError::GameNotFound => "game_not_found",
+            GameError::InvalidName => "invalid_name",
+            GameError::ShuttingDown => "shutting_down",
+            GameError::AdviceDisabled => "advice_disabled",
+            GameError::Rejected(_) => "rejected",
+        }
+    }
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameError::RoomNotFound => f.write_str("Room not found"),
+            GameError::NotRoomOwner => f.write_str("Only the room owner can do that"),
+            GameError::NotInRoom => f.write_str("Not a member of the room"),
+            GameError::NotYourTurn => f.write_str("It is not your turn"),
+            GameError::TooManyRooms => f.write_str("The server can't host any more rooms"),
+            GameError::RoomFull => f.write_str("The room is full"),
+            GameError::InvalidToken => f.write_str("The reconnect token is not valid"),
+            GameError::GameNotFound => f.write_str("Game not found"),
+            GameError::InvalidName => f.write_str("Names must be 1 to 32 printable characters"),
+            GameError::ShuttingDown => f.write_str("The server is shutting down"),
+            GameError::AdviceDisabled => f.write_str("Advice is turned off in this room"),
+            GameError::Rejected(reason) => f.write_str(reason),
+        }
+    }
+}
+
+impl From<&'static str> for GameError {
+    fn from(reason: &'static str) -> Self {
+        GameError::Rejected(reason)
+    }
+}
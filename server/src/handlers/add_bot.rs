@@ -0,0 +1,49 @@
+use actix::prelude::*;
+use tracing::{info, instrument};
+use uuid::Uuid;
+use the_game_core::strategy::BuiltinStrategy;
+use crate::error::GameError;
+use crate::identity;
+use crate::server;
+
+#[derive(Message)]
+#[rtype(result = "Result<Uuid, GameError>")]
+pub struct AddBot {
+    pub room_id: Uuid,
+    pub session: Uuid,
+    pub strategy: BuiltinStrategy,
+}
+
+/// Seats a computer player playing with the given strategy, returning its id.
+/// Only the room owner may add bots, before the game has started.
+impl Handler<AddBot> for server::GameServer {
+    type Result = Result<Uuid, GameError>;
+
+    /// Handles `AddBot` message.
+    #[instrument(name = "add_bot", skip_all, fields(session = %msg.session, room = %msg.room_id, strategy = %msg.strategy), err(Display, level = "warn"))]
+    fn handle(&mut self, msg: AddBot, _: &mut Context<Self>) -> Self::Result {
+        let result = self.add_bot(&msg);
+        self.metrics.observe("add_bot", &result);
+        result
+    }
+}
+
+impl server::GameServer {
+    fn add_bot(&mut self, msg: &AddBot) -> Result<Uuid, GameError> {
+        let max_players = self.config.max_players;
+        let room = self.room_mut(&msg.room_id)?;
+        if Some(&msg.session) != room.get_owner() {
+            return Err(GameError::NotRoomOwner);
+        }
+        if room.len() >= max_players {
+            return Err(GameError::RoomFull);
+        }
+
+        let bot = Uuid::new_v4();
+        room.join(bot, identity::bot_name(&bot))?;
+        room.bots.insert(bot, msg.strategy);
+        info!(%bot, players = room.len(), "Bot joined the room");
+        self.touch(msg.room_id);
+        Ok(bot)
+    }
+}
@@ -0,0 +1,23 @@
+use actix::prelude::*;
+use uuid::Uuid;
+use the_game_core::analysis::{analyze, Analysis};
+use crate::error::GameError;
+use crate::server;
+
+#[derive(Message)]
+#[rtype(result = "Result<Analysis, GameError>")]
+pub struct AnalyzeGame(pub Uuid);
+
+/// Reviews a finished game turn by turn, replaying its log from its seed.
+impl Handler<AnalyzeGame> for server::GameServer {
+    type Result = Result<Analysis, GameError>;
+
+    /// Handles `AnalyzeGame` message.
+    fn handle(&mut self, msg: AnalyzeGame, _: &mut Context<Self>) -> Self::Result {
+        let result = self.history.game(&msg.0)
+            .ok_or(GameError::GameNotFound)
+            .and_then(|game| analyze(game.summary.variant, game.summary.seed, &game.log).map_err(GameError::Rejected));
+        self.metrics.observe("analyze_game", &result);
+        result
+    }
+}
@@ -1,5 +1,8 @@
 use actix::prelude::*;
+use actix_web_actors::ws::{CloseCode, CloseReason};
+use tracing::info;
 use uuid::Uuid;
+use crate::codec::{Codec, Frame};
 use crate::server;
 
 #[derive(Message)]
@@ -7,13 +10,32 @@ use crate::server;
 pub struct Connect {
     pub id: Uuid,
     pub addr: Recipient<server::Message>,
+    pub codec: Codec,
+    /// The reconnect token of the session.
+    pub token: String,
+    /// The display name the session has proven to own, if any.
+    pub name: Option<String>,
 }
 
 impl Handler<Connect> for server::GameServer {
     type Result = ();
 
     /// Handles `Connect` message.
+    /// Connecting again replaces the send handle and the codec of the session.
+    /// Another connection holding the same identity is closed.
     fn handle(&mut self, msg: Connect, _: &mut Context<Self>) {
-        self.sessions.insert(msg.id, msg.addr);
+        info!(session = %msg.id, codec = ?msg.codec, "Session connected");
+        self.metrics.message("connect");
+        if let Some(previous) = self.sessions.get(&msg.id).filter(|previous| previous.addr != msg.addr) {
+            info!(session = %msg.id, "Closing the previous connection of the session");
+            let reason = CloseReason { code: CloseCode::Policy, description: Some("Signed in from another connection".to_string()) };
+            previous.addr.do_send(server::Message(Frame::Close(Some(reason))));
+        }
+
+        self.sessions.insert(msg.id, server::Client { addr: msg.addr, codec: msg.codec });
+        self.tokens.insert(msg.id, msg.token);
+        if let Some(name) = msg.name {
+            self.names.insert(msg.id, name);
+        }
     }
 }
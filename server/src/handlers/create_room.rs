@@ -1,18 +1,24 @@
 use actix::prelude::*;
+use tracing::{info, instrument};
 use uuid::Uuid;
+use crate::error::GameError;
 use crate::server;
 
 #[derive(Message)]
-#[rtype(result = "Result<Uuid, &'static str>")]
-pub struct CreateRoom(pub Uuid);
+#[rtype(result = "Result<Uuid, GameError>")]
+pub struct CreateRoom(pub Option<Uuid>);
 
 impl Handler<CreateRoom> for server::GameServer {
-    type Result = Result<Uuid, &'static str>;
+    type Result = Result<Uuid, GameError>;
 
     /// Handles `CreateRoom` message.
+    #[instrument(name = "create_room", skip_all, fields(session = msg.0.map(tracing::field::display)), err(Display, level = "warn"))]
     fn handle(&mut self, msg: CreateRoom, _: &mut Context<Self>) -> Self::Result {
         let room_id = Uuid::new_v4();
-        self.create_room(room_id, msg.0);
+        let result = self.create_room(room_id, msg.0);
+        self.metrics.observe("create_room", &result);
+        result?;
+        info!(room = %room_id, "Room created");
         Ok(room_id)
     }
 }
@@ -1,10 +1,12 @@
 use actix::prelude::*;
+use tracing::{info, instrument};
 use uuid::Uuid;
+use crate::error::GameError;
 use crate::messages::MessageToClient::DiscardOne;
 use crate::server;
 
 #[derive(Message)]
-#[rtype(result = "Result<(), &'static str>")]
+#[rtype(result = "Result<(), GameError>")]
 pub struct DiscardCard {
     pub card: u8,
     pub position: usize,
@@ -14,18 +16,39 @@ pub struct DiscardCard {
 
 /// Discards a card. Returning `Ok` once a card has been discarded, or `Err` otherwise.
 impl Handler<DiscardCard> for server::GameServer {
-    type Result = Result<(), &'static str>;
+    type Result = Result<(), GameError>;
 
     /// Handles `DiscardCard` message.
+    #[instrument(
+        name = "discard_card",
+        skip_all,
+        fields(session = %msg.session, room = %msg.room_id, card = msg.card, pile = msg.position),
+        err(Display, level = "warn"),
+    )]
     fn handle(&mut self, msg: DiscardCard, _: &mut Self::Context) -> Self::Result {
-        if let Some(room) = self.rooms.get_mut(&msg.room_id) {
-            if let Err(err) = room.game.discard_card(msg.position, &msg.card.into()) {
-                return Err(err);
-            }
+        let result = self.discard_card(&msg);
+        self.metrics.observe("discard_card", &result);
+        result
+    }
+}
 
-            self.send_message(&msg.room_id, &DiscardOne(msg.card, msg.position));
+impl server::GameServer {
+    fn discard_card(&mut self, msg: &DiscardCard) -> Result<(), GameError> {
+        if !self.room(&msg.room_id)?.is_current_player(&msg.session) {
+            return Err(GameError::NotYourTurn);
         }
 
+        self.play_card(msg.room_id, msg.position, msg.card)
+    }
+
+    /// Plays a card of the current player of the given room onto the given discard pile.
+    pub fn play_card(&mut self, room_id: Uuid, position: usize, card: u8) -> Result<(), GameError> {
+        let room = self.room_mut(&room_id)?;
+        room.game.discard_card(position, &card.into())?;
+        info!(turn = room.game.turn, "Card discarded");
+        self.touch(room_id);
+        self.send_message(&room_id, &DiscardOne { card, position });
+        self.announce_game_over(&room_id);
         Ok(())
     }
 }
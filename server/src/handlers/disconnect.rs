@@ -1,16 +1,41 @@
 use actix::prelude::*;
+use tracing::info;
 use uuid::Uuid;
 use crate::server;
 
 #[derive(Message)]
 #[rtype(result = "()")]
-pub struct Disconnect(pub Uuid);
+pub struct Disconnect {
+    pub id: Uuid,
+    /// The send handle of the session, which may have been replaced by another connection.
+    pub addr: Recipient<server::Message>,
+}
 
 impl Handler<Disconnect> for server::GameServer {
     type Result = ();
 
     /// Handles `Disconnect` message.
-    fn handle(&mut self, msg: Disconnect, _: &mut Context<Self>) {
-        self.sessions.remove(&msg.0);
+    fn handle(&mut self, msg: Disconnect, ctx: &mut Context<Self>) {
+        self.metrics.message("disconnect");
+
+        // A session that took over the identity keeps it.
+        if self.sessions.get(&msg.id).is_some_and(|client| client.addr != msg.addr) {
+            return;
+        }
+
+        if self.sessions.remove(&msg.id).is_some() {
+            info!(session = %msg.id, "Session disconnected");
+        }
+
+        self.names.remove(&msg.id);
+
+        // The token is kept as long as the session has a seat to take back,
+        // which it keeps for the reconnect grace period.
+        if self.rooms.values().any(|room| room.contains(&msg.id)) {
+            let session_id = msg.id;
+            ctx.run_later(self.config.reconnect_grace, move |act, _| act.expire_seats(&session_id));
+        } else {
+            self.tokens.remove(&msg.id);
+        }
     }
 }
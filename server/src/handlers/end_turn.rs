@@ -1,26 +1,59 @@
 use actix::prelude::*;
+use tracing::{info, instrument};
 use uuid::Uuid;
-use crate::messages::MessageToClient::DealtHand;
+use crate::error::GameError;
+use crate::messages::MessageToClient;
 use crate::server;
 
 #[derive(Message)]
-#[rtype(result = "Result<(), &'static str>")]
+#[rtype(result = "Result<(), GameError>")]
 pub struct EndTurn {
     pub room_id: Uuid,
     pub session: Uuid,
 }
 
 impl Handler<EndTurn> for server::GameServer {
-    type Result = Result<(), &'static str>;
+    type Result = Result<(), GameError>;
 
     /// Handles `EndTurn` message.
-    fn handle(&mut self, msg: EndTurn, _: &mut Self::Context) -> Self::Result {
-        if let Some(room) = self.rooms.get_mut(&msg.room_id) {
-            let dealt_cards = room.game.end_turn()?;
+    #[instrument(name = "end_turn", skip_all, fields(session = %msg.session, room = %msg.room_id), err(Display, level = "warn"))]
+    fn handle(&mut self, msg: EndTurn, ctx: &mut Self::Context) -> Self::Result {
+        let result = self.end_turn(&msg);
+        self.metrics.observe("end_turn", &result);
+        if result.is_ok() {
+            self.start_turn(msg.room_id, ctx);
+        }
+
+        result
+    }
+}
+
+impl server::GameServer {
+    fn end_turn(&mut self, msg: &EndTurn) -> Result<(), GameError> {
+        if !self.room(&msg.room_id)?.is_current_player(&msg.session) {
+            return Err(GameError::NotYourTurn);
+        }
+
+        self.pass_turn(msg.room_id)
+    }
+
+    /// Ends the turn of the current player of the given room, dealing them new cards.
+    pub fn pass_turn(&mut self, room_id: Uuid) -> Result<(), GameError> {
+        let room = self.room_mut(&room_id)?;
+        let session = room.players.iter().nth(room.game.current_player_index).copied();
+        let dealt_cards = room.end_turn()?;
+        info!(turn = room.game.turn, drawn = dealt_cards.len(), "Turn ended");
+        let player = room.game.current_player_index;
+        let name = room.game.players()[player].name.clone();
+        self.touch(room_id);
+
+        if let Some(session) = session {
             let cards = dealt_cards.iter().map(|x| x.0).collect::<Vec<_>>();
-            self.send_message_to(&msg.session, &DealtHand(cards));
+            self.send_message_to(&session, &MessageToClient::DealtHand { cards });
         }
 
+        self.send_message(&room_id, &MessageToClient::EndTurn { player, name });
+        self.announce_game_over(&room_id);
         Ok(())
     }
 }
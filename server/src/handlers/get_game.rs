@@ -0,0 +1,20 @@
+use actix::prelude::*;
+use uuid::Uuid;
+use crate::error::GameError;
+use crate::history::GameRecord;
+use crate::server;
+
+#[derive(Message)]
+#[rtype(result = "Result<GameRecord, GameError>")]
+pub struct GetGame(pub Uuid);
+
+impl Handler<GetGame> for server::GameServer {
+    type Result = Result<GameRecord, GameError>;
+
+    /// Handles `GetGame` message.
+    fn handle(&mut self, msg: GetGame, _: &mut Context<Self>) -> Self::Result {
+        let result = self.history.game(&msg.0).cloned().ok_or(GameError::GameNotFound);
+        self.metrics.observe("get_game", &result);
+        result
+    }
+}
@@ -0,0 +1,20 @@
+use actix::prelude::*;
+use tracing::instrument;
+use uuid::Uuid;
+use crate::error::GameError;
+use crate::room::RoomView;
+use crate::server;
+
+#[derive(Message)]
+#[rtype(result = "Result<RoomView, GameError>")]
+pub struct GetRoom(pub Uuid);
+
+impl Handler<GetRoom> for server::GameServer {
+    type Result = Result<RoomView, GameError>;
+
+    /// Handles `GetRoom` message.
+    #[instrument(name = "get_room", skip_all, fields(room = %msg.0), err(Display, level = "debug"))]
+    fn handle(&mut self, msg: GetRoom, _: &mut Context<Self>) -> Self::Result {
+        Ok(self.room(&msg.0)?.view(msg.0))
+    }
+}
@@ -0,0 +1,23 @@
+use actix::prelude::*;
+use serde::Serialize;
+use crate::server;
+
+#[derive(Message)]
+#[rtype(result = "HealthStatus")]
+pub struct Health;
+
+/// A summary proving that the game server is responsive.
+#[derive(Debug, Serialize)]
+pub struct HealthStatus {
+    pub sessions: usize,
+    pub rooms: usize,
+}
+
+impl Handler<Health> for server::GameServer {
+    type Result = MessageResult<Health>;
+
+    /// Handles `Health` message.
+    fn handle(&mut self, _: Health, _: &mut Context<Self>) -> Self::Result {
+        MessageResult(HealthStatus { sessions: self.sessions.len(), rooms: self.rooms.len() })
+    }
+}
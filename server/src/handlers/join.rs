@@ -1,21 +1,40 @@
 use actix::prelude::*;
+use tracing::{info, instrument};
 use uuid::Uuid;
+use crate::error::GameError;
 use crate::server;
 
 #[derive(Message)]
-#[rtype(result = "()")]
+#[rtype(result = "Result<(), GameError>")]
 pub struct JoinRoom {
     pub room_id: Uuid,
     pub session: Uuid,
 }
 
 impl Handler<JoinRoom> for server::GameServer {
-    type Result = ();
+    type Result = Result<(), GameError>;
 
     /// Handles `JoinRoom` message.
-    fn handle(&mut self, msg: JoinRoom, _: &mut Context<Self>) {
-        if let Some(room) = self.rooms.get_mut(&msg.room_id) {
-            room.join(msg.session)
+    #[instrument(name = "join_room", skip_all, fields(session = %msg.session, room = %msg.room_id), err(Display, level = "warn"))]
+    fn handle(&mut self, msg: JoinRoom, _: &mut Context<Self>) -> Self::Result {
+        let result = self.join_room(&msg);
+        self.metrics.observe("join_room", &result);
+        result
+    }
+}
+
+impl server::GameServer {
+    fn join_room(&mut self, msg: &JoinRoom) -> Result<(), GameError> {
+        let max_players = self.config.max_players;
+        let name = self.name_of(&msg.session);
+        let room = self.room_mut(&msg.room_id)?;
+        if room.len() >= max_players && !room.contains(&msg.session) {
+            return Err(GameError::RoomFull);
         }
+
+        room.join(msg.session, name)?;
+        info!(players = room.len(), "Player joined the room");
+        self.touch(msg.room_id);
+        Ok(())
     }
 }
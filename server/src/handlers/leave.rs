@@ -0,0 +1,38 @@
+use actix::prelude::*;
+use tracing::{info, instrument};
+use uuid::Uuid;
+use crate::error::GameError;
+use crate::server;
+
+#[derive(Message)]
+#[rtype(result = "Result<(), GameError>")]
+pub struct LeaveRoom {
+    pub room_id: Uuid,
+    pub session: Uuid,
+}
+
+impl Handler<LeaveRoom> for server::GameServer {
+    type Result = Result<(), GameError>;
+
+    /// Handles `LeaveRoom` message.
+    #[instrument(name = "leave_room", skip_all, fields(session = %msg.session, room = %msg.room_id), err(Display, level = "warn"))]
+    fn handle(&mut self, msg: LeaveRoom, _: &mut Context<Self>) -> Self::Result {
+        let result = self.leave_room(&msg);
+        self.metrics.observe("leave_room", &result);
+        result
+    }
+}
+
+impl server::GameServer {
+    fn leave_room(&mut self, msg: &LeaveRoom) -> Result<(), GameError> {
+        let room = self.room_mut(&msg.room_id)?;
+        if !room.contains(&msg.session) {
+            return Err(GameError::NotInRoom);
+        }
+
+        room.leave(&msg.session)?;
+        info!(players = room.len(), "Player left the room");
+        self.touch(msg.room_id);
+        Ok(())
+    }
+}
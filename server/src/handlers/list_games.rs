@@ -0,0 +1,19 @@
+use actix::prelude::*;
+use uuid::Uuid;
+use crate::history::GameSummary;
+use crate::server;
+
+/// Lists the finished games the given player took part in, or all of them.
+#[derive(Message)]
+#[rtype(result = "Vec<GameSummary>")]
+pub struct ListGames(pub Option<Uuid>);
+
+impl Handler<ListGames> for server::GameServer {
+    type Result = MessageResult<ListGames>;
+
+    /// Handles `ListGames` message.
+    fn handle(&mut self, msg: ListGames, _: &mut Context<Self>) -> Self::Result {
+        self.metrics.message("list_games");
+        MessageResult(self.history.games(msg.0.as_ref()).cloned().collect())
+    }
+}
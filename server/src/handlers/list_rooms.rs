@@ -0,0 +1,16 @@
+use actix::prelude::*;
+use crate::room::RoomView;
+use crate::server;
+
+#[derive(Message)]
+#[rtype(result = "Vec<RoomView>")]
+pub struct ListRooms;
+
+impl Handler<ListRooms> for server::GameServer {
+    type Result = MessageResult<ListRooms>;
+
+    /// Handles `ListRooms` message.
+    fn handle(&mut self, _: ListRooms, _: &mut Context<Self>) -> Self::Result {
+        MessageResult(self.rooms.iter().map(|(id, room)| room.view(*id)).collect())
+    }
+}
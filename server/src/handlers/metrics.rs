@@ -0,0 +1,16 @@
+use actix::prelude::*;
+use crate::server;
+
+#[derive(Message)]
+#[rtype(result = "String")]
+pub struct GetMetrics;
+
+/// Renders the metrics of the game server in the Prometheus text format.
+impl Handler<GetMetrics> for server::GameServer {
+    type Result = MessageResult<GetMetrics>;
+
+    /// Handles `GetMetrics` message.
+    fn handle(&mut self, _: GetMetrics, _: &mut Context<Self>) -> Self::Result {
+        MessageResult(self.metrics.render(self.sessions.len(), self.rooms.values()))
+    }
+}
@@ -1,21 +1,55 @@
+pub mod add_bot;
+pub mod analyze_game;
 pub mod connect;
 pub mod create_room;
 pub mod discard;
 pub mod disconnect;
 pub mod end_turn;
+pub mod get_game;
+pub mod get_room;
+pub mod health;
 pub mod join;
+pub mod leave;
+pub mod list_games;
+pub mod list_rooms;
+pub mod metrics;
+pub mod player_stats;
+pub mod reconnect;
+pub mod register;
+pub mod request_advice;
+pub mod room_history;
+pub mod set_advice;
+pub mod set_turn_clock;
+pub mod shutdown;
 pub mod start;
 
 pub mod prelude {
     //! ```
     //! # #![allow(unused_imports)]
-    //! use crate::handlers::prelude::*;
+    //! use the_game_server::handlers::prelude::*;
     //! ```
+    pub use crate::handlers::add_bot::*;
+    pub use crate::handlers::analyze_game::*;
     pub use crate::handlers::connect::*;
     pub use crate::handlers::create_room::*;
     pub use crate::handlers::discard::*;
     pub use crate::handlers::disconnect::*;
     pub use crate::handlers::join::*;
+    pub use crate::handlers::leave::*;
     pub use crate::handlers::start::*;
     pub use crate::handlers::end_turn::*;
+    pub use crate::handlers::get_game::*;
+    pub use crate::handlers::get_room::*;
+    pub use crate::handlers::health::*;
+    pub use crate::handlers::list_games::*;
+    pub use crate::handlers::list_rooms::*;
+    pub use crate::handlers::metrics::*;
+    pub use crate::handlers::player_stats::*;
+    pub use crate::handlers::reconnect::*;
+    pub use crate::handlers::register::*;
+    pub use crate::handlers::request_advice::*;
+    pub use crate::handlers::room_history::*;
+    pub use crate::handlers::set_advice::*;
+    pub use crate::handlers::set_turn_clock::*;
+    pub use crate::handlers::shutdown::*;
 }
@@ -0,0 +1,18 @@
+use actix::prelude::*;
+use uuid::Uuid;
+use crate::history::PlayerStats;
+use crate::server;
+
+#[derive(Message)]
+#[rtype(result = "PlayerStats")]
+pub struct GetPlayerStats(pub Uuid);
+
+impl Handler<GetPlayerStats> for server::GameServer {
+    type Result = MessageResult<GetPlayerStats>;
+
+    /// Handles `GetPlayerStats` message.
+    fn handle(&mut self, msg: GetPlayerStats, _: &mut Context<Self>) -> Self::Result {
+        self.metrics.message("player_stats");
+        MessageResult(self.history.stats(msg.0))
+    }
+}
@@ -0,0 +1,58 @@
+use actix::prelude::*;
+use tracing::{info, instrument};
+use uuid::Uuid;
+use crate::error::GameError;
+use crate::messages::MessageToClient::DealtHand;
+use crate::messages::ReconnectResponse;
+use crate::server;
+
+#[derive(Message)]
+#[rtype(result = "Result<ReconnectResponse, GameError>")]
+pub struct Reconnect {
+    pub token: String,
+    pub session: Uuid,
+}
+
+/// Gives the seats of the session the token was issued to to the given session,
+/// and sends it the cards in its hands.
+/// A token can only be used once.
+impl Handler<Reconnect> for server::GameServer {
+    type Result = Result<ReconnectResponse, GameError>;
+
+    /// Handles `Reconnect` message.
+    #[instrument(name = "reconnect", skip_all, fields(session = %msg.session), err(Display, level = "warn"))]
+    fn handle(&mut self, msg: Reconnect, _: &mut Context<Self>) -> Self::Result {
+        let result = self.reconnect(&msg);
+        self.metrics.observe("reconnect", &result);
+        result
+    }
+}
+
+impl server::GameServer {
+    fn reconnect(&mut self, msg: &Reconnect) -> Result<ReconnectResponse, GameError> {
+        let previous = self.tokens.iter()
+            .find(|(id, token)| **id != msg.session && **token == msg.token)
+            .map(|(id, _)| *id)
+            .ok_or(GameError::InvalidToken)?;
+
+        self.tokens.remove(&previous);
+        let mut rooms = Vec::new();
+        for (room_id, room) in self.rooms.iter_mut() {
+            if room.replace_player(&previous, msg.session) {
+                rooms.push(*room_id);
+            }
+        }
+
+        info!(%previous, rooms = rooms.len(), "Session reconnected");
+        for room_id in &rooms {
+            self.touch(*room_id);
+            let room = &self.rooms[room_id];
+            if let Some(player) = room.player_index(&msg.session).and_then(|index| room.game.players().get(index)) {
+                let cards = player.hand().iter().map(|x| x.0).collect::<Vec<_>>();
+                self.send_message_to(&msg.session, &DealtHand { cards });
+            }
+        }
+
+        Ok(ReconnectResponse { rooms })
+    }
+}
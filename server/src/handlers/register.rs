@@ -0,0 +1,51 @@
+use actix::prelude::*;
+use tracing::{info, instrument};
+use uuid::Uuid;
+use crate::error::GameError;
+use crate::identity::{self, Identity};
+use crate::messages::RegisterResponse;
+use crate::server;
+
+#[derive(Message)]
+#[rtype(result = "Result<RegisterResponse, GameError>")]
+pub struct Register {
+    pub name: String,
+    pub session: Uuid,
+}
+
+/// Gives the session a display name, shown in the rooms it sits in,
+/// and signs a token letting later sessions take this identity back.
+impl Handler<Register> for server::GameServer {
+    type Result = Result<RegisterResponse, GameError>;
+
+    /// Handles `Register` message.
+    #[instrument(name = "register", skip_all, fields(session = %msg.session), err(Display, level = "warn"))]
+    fn handle(&mut self, msg: Register, _: &mut Context<Self>) -> Self::Result {
+        let result = self.register(&msg);
+        self.metrics.observe("register", &result);
+        result
+    }
+}
+
+impl server::GameServer {
+    fn register(&mut self, msg: &Register) -> Result<RegisterResponse, GameError> {
+        let name = identity::validate_name(&msg.name)?.to_string();
+        self.names.insert(msg.session, name.clone());
+        let rooms = self.rooms.iter_mut()
+            .filter(|(_, room)| room.contains(&msg.session))
+            .map(|(room_id, room)| {
+                room.rename(&msg.session, &name);
+                *room_id
+            })
+            .collect::<Vec<_>>();
+
+        for room_id in rooms {
+            self.touch(room_id);
+        }
+
+        info!(name, "Player registered");
+        let identity = Identity { id: msg.session, name };
+        let player_token = identity.sign(&self.config.secret);
+        Ok(RegisterResponse { player_id: identity.id, name: identity.name, player_token })
+    }
+}
@@ -0,0 +1,63 @@
+use actix::prelude::*;
+use tracing::{info, instrument};
+use uuid::Uuid;
+use the_game_core::advice::advise;
+use the_game_core::strategy::Decision;
+use crate::error::GameError;
+use crate::messages::{AdviceResponse, Suggestion};
+use crate::server;
+
+#[derive(Message)]
+#[rtype(result = "Result<AdviceResponse, GameError>")]
+pub struct RequestAdvice {
+    pub room_id: Uuid,
+    pub session: Uuid,
+}
+
+/// Suggests the moves of the player who has the turn, the best first.
+/// Advice must be turned on for the room, and every request is written to the game log.
+impl Handler<RequestAdvice> for server::GameServer {
+    type Result = Result<AdviceResponse, GameError>;
+
+    /// Handles `RequestAdvice` message.
+    #[instrument(name = "request_advice", skip_all, fields(session = %msg.session, room = %msg.room_id), err(Display, level = "warn"))]
+    fn handle(&mut self, msg: RequestAdvice, _: &mut Context<Self>) -> Self::Result {
+        let result = self.request_advice(&msg);
+        self.metrics.observe("request_advice", &result);
+        result
+    }
+}
+
+impl server::GameServer {
+    fn request_advice(&mut self, msg: &RequestAdvice) -> Result<AdviceResponse, GameError> {
+        let room = self.room_mut(&msg.room_id)?;
+        if !room.contains(&msg.session) {
+            return Err(GameError::NotInRoom);
+        }
+        if !room.advice {
+            return Err(GameError::AdviceDisabled);
+        }
+        if !room.game.is_playing() {
+            return Err(GameError::Rejected("Game is not running"));
+        }
+        if !room.is_current_player(&msg.session) {
+            return Err(GameError::NotYourTurn);
+        }
+
+        let suggestions = advise(&room.game.view()).into_iter()
+            .map(|suggestion| {
+                let (card, position) = match suggestion.decision {
+                    Decision::Play { pile, card } => (Some(card.0), Some(pile)),
+                    Decision::EndTurn => (None, None),
+                };
+
+                Suggestion { card, position, explanation: suggestion.explanation }
+            })
+            .collect::<Vec<_>>();
+
+        room.game.record_advice();
+        info!(turn = room.game.turn, suggestions = suggestions.len(), "Advice given");
+        self.touch(msg.room_id);
+        Ok(AdviceResponse { suggestions })
+    }
+}
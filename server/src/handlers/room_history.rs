@@ -0,0 +1,31 @@
+use actix::prelude::*;
+use tracing::instrument;
+use uuid::Uuid;
+use the_game_core::event::Event;
+use crate::error::GameError;
+use crate::server;
+
+#[derive(Message)]
+#[rtype(result = "Result<Vec<Event>, GameError>")]
+pub struct RoomHistory(pub Uuid);
+
+impl Handler<RoomHistory> for server::GameServer {
+    type Result = Result<Vec<Event>, GameError>;
+
+    /// Handles `RoomHistory` message.
+    #[instrument(name = "room_history", skip_all, fields(room = %msg.0), err(Display, level = "debug"))]
+    fn handle(&mut self, msg: RoomHistory, _: &mut Context<Self>) -> Self::Result {
+        let game = &self.room(&msg.0)?.game;
+        let mut log = game.log().to_vec();
+        if game.is_playing() {
+            // The cards drawn stay known only to the players who drew them until the game is over.
+            for event in &mut log {
+                if let Event::TurnEnded { drawn, .. } = event {
+                    drawn.clear();
+                }
+            }
+        }
+
+        Ok(log)
+    }
+}
@@ -0,0 +1,44 @@
+use actix::prelude::*;
+use tracing::{info, instrument};
+use uuid::Uuid;
+use crate::error::GameError;
+use crate::server;
+
+#[derive(Message)]
+#[rtype(result = "Result<(), GameError>")]
+pub struct SetAdvice {
+    pub room_id: Uuid,
+    pub session: Uuid,
+    pub enabled: bool,
+}
+
+/// Lets the players of a room ask for advice on their turns, or stops them from doing so.
+/// Only the room owner may change it, before the game has started.
+impl Handler<SetAdvice> for server::GameServer {
+    type Result = Result<(), GameError>;
+
+    /// Handles `SetAdvice` message.
+    #[instrument(name = "set_advice", skip_all, fields(session = %msg.session, room = %msg.room_id, enabled = msg.enabled), err(Display, level = "warn"))]
+    fn handle(&mut self, msg: SetAdvice, _: &mut Context<Self>) -> Self::Result {
+        let result = self.set_advice(&msg);
+        self.metrics.observe("set_advice", &result);
+        result
+    }
+}
+
+impl server::GameServer {
+    fn set_advice(&mut self, msg: &SetAdvice) -> Result<(), GameError> {
+        let room = self.room_mut(&msg.room_id)?;
+        if Some(&msg.session) != room.get_owner() {
+            return Err(GameError::NotRoomOwner);
+        }
+        if !room.game.is_created() {
+            return Err(GameError::Rejected("Can't change the advice of a running game"));
+        }
+
+        room.advice = msg.enabled;
+        info!("Advice set");
+        self.touch(msg.room_id);
+        Ok(())
+    }
+}
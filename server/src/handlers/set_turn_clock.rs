@@ -0,0 +1,48 @@
+use actix::prelude::*;
+use tracing::{info, instrument};
+use uuid::Uuid;
+use crate::clock::TurnClock;
+use crate::error::GameError;
+use crate::server;
+
+#[derive(Message)]
+#[rtype(result = "Result<(), GameError>")]
+pub struct SetTurnClock {
+    pub room_id: Uuid,
+    pub session: Uuid,
+    pub clock: Option<TurnClock>,
+}
+
+/// Limits how long each turn of the game may last, or lifts the limit.
+/// Only the room owner may set the clock, before the game has started.
+impl Handler<SetTurnClock> for server::GameServer {
+    type Result = Result<(), GameError>;
+
+    /// Handles `SetTurnClock` message.
+    #[instrument(name = "set_turn_clock", skip_all, fields(session = %msg.session, room = %msg.room_id), err(Display, level = "warn"))]
+    fn handle(&mut self, msg: SetTurnClock, _: &mut Context<Self>) -> Self::Result {
+        let result = self.set_turn_clock(&msg);
+        self.metrics.observe("set_turn_clock", &result);
+        result
+    }
+}
+
+impl server::GameServer {
+    fn set_turn_clock(&mut self, msg: &SetTurnClock) -> Result<(), GameError> {
+        let room = self.room_mut(&msg.room_id)?;
+        if Some(&msg.session) != room.get_owner() {
+            return Err(GameError::NotRoomOwner);
+        }
+        if !room.game.is_created() {
+            return Err(GameError::Rejected("Can't change the clock of a running game"));
+        }
+        if msg.clock.is_some_and(|clock| clock.time_limit == 0) {
+            return Err(GameError::Rejected("The time limit must be at least one second"));
+        }
+
+        room.turn_clock = msg.clock;
+        info!(clock = ?msg.clock, "Turn clock set");
+        self.touch(msg.room_id);
+        Ok(())
+    }
+}
@@ -0,0 +1,37 @@
+use actix::prelude::*;
+use actix_web_actors::ws::{CloseCode, CloseReason};
+use tracing::info;
+use crate::codec::Frame;
+use crate::messages::MessageToClient::ServerShuttingDown;
+use crate::server;
+
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct Shutdown;
+
+/// Stops taking new rooms, warns every session, saves every room,
+/// and closes the sessions once the countdown is over.
+impl Handler<Shutdown> for server::GameServer {
+    type Result = ResponseActFuture<Self, ()>;
+
+    /// Handles `Shutdown` message.
+    fn handle(&mut self, _: Shutdown, _: &mut Context<Self>) -> Self::Result {
+        let countdown = self.config.shutdown_countdown;
+        info!(sessions = self.sessions.len(), rooms = self.rooms.len(), ?countdown, "Shutting down");
+        self.shutting_down = true;
+        self.broadcast(&ServerShuttingDown { seconds: countdown.as_secs() });
+        self.snapshot();
+
+        let close = async move { actix::clock::sleep(countdown).await };
+        Box::pin(close.into_actor(self).map(|_, act, _| {
+            // Games went on during the countdown.
+            act.snapshot();
+            let reason = CloseReason { code: CloseCode::Away, description: Some("Server shutting down".to_string()) };
+            for client in act.sessions.values() {
+                client.addr.do_send(server::Message(Frame::Close(Some(reason.clone()))));
+            }
+
+            info!(sessions = act.sessions.len(), "Sessions closed");
+        }))
+    }
+}
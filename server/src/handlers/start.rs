@@ -1,10 +1,12 @@
 use actix::prelude::*;
+use tracing::{info, instrument};
 use uuid::Uuid;
+use crate::error::GameError;
 use crate::messages::MessageToClient::DealtHand;
 use crate::server;
 
 #[derive(Message)]
-#[rtype(result = "Result<(), &'static str>")]
+#[rtype(result = "Result<(), GameError>")]
 pub struct StartGame {
     pub room_id: Uuid,
     pub session: Uuid,
@@ -14,22 +16,38 @@ pub struct StartGame {
 /// deals the players opening hand.
 /// Only the room owner has the privilege to start the game.
 impl Handler<StartGame> for server::GameServer {
-    type Result = Result<(), &'static str>;
+    type Result = Result<(), GameError>;
 
     /// Handles `StartGame` message.
-    fn handle(&mut self, msg: StartGame, _: &mut Context<Self>) -> Self::Result {
-        if let Some(room) = self.rooms.get_mut(&msg.room_id) {
-            if Some(&msg.session) == room.get_owner() {
-                if let Err(err) = room.start_game() {
-                    return Err(err);
-                }
-
-                // Sends each player an event containing a set of cards in their hand.
-                for (id, player) in room.players.clone().iter().zip(room.game.clone().players()) {
-                    let cards = player.hand().iter().map(|x| x.0).collect::<Vec<_>>();
-                    self.send_message_to(id, &DealtHand(cards))
-                }
-            }
+    #[instrument(name = "start_game", skip_all, fields(session = %msg.session, room = %msg.room_id), err(Display, level = "warn"))]
+    fn handle(&mut self, msg: StartGame, ctx: &mut Context<Self>) -> Self::Result {
+        let result = self.start_game(&msg);
+        self.metrics.observe("start_game", &result);
+        if result.is_ok() {
+            self.start_turn(msg.room_id, ctx);
+        }
+
+        result
+    }
+}
+
+impl server::GameServer {
+    fn start_game(&mut self, msg: &StartGame) -> Result<(), GameError> {
+        let room = self.room_mut(&msg.room_id)?;
+        if Some(&msg.session) != room.get_owner() {
+            return Err(GameError::NotRoomOwner);
+        }
+
+        room.start_game()?;
+        info!(players = room.len(), variant = %room.game.variant(), "Game started");
+        self.metrics.games_started += 1;
+        self.touch(msg.room_id);
+
+        // Sends each player an event containing a set of cards in their hand.
+        let room = &self.rooms[&msg.room_id];
+        for (id, player) in room.players.iter().zip(room.game.players()) {
+            let cards = player.hand().iter().map(|x| x.0).collect::<Vec<_>>();
+            self.send_message_to(id, &DealtHand { cards })
         }
 
         Ok(())
@@ -0,0 +1,175 @@
+//! The record of finished games, and the statistics of the players drawn from it.
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+use uuid::Uuid;
+use the_game_core::event::Event;
+use the_game_core::game::Outcome;
+use the_game_core::variant::Variant;
+
+/// The name of the file finished games are appended to, one JSON object per line.
+const HISTORY_FILE: &str = "history.jsonl";
+
+/// The outline of a finished game.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct GameSummary {
+    pub id: Uuid,
+    /// The room the game was played in.
+    pub room_id: Uuid,
+    pub variant: Variant,
+    /// The seed the deck was shuffled with.
+    pub seed: u64,
+    /// The players in the order they were seated.
+    pub players: Vec<Uuid>,
+    /// The display names of the players, in the same order.
+    #[serde(default)]
+    pub names: Vec<String>,
+    pub outcome: Outcome,
+    /// The number of cards left unplayed.
+    pub score: usize,
+    /// When the game started, in seconds since the Unix epoch.
+    pub started_at: u64,
+    /// How long the game lasted, in seconds.
+    pub duration: u64,
+    /// Whether the game was ended by the server before it was over, in which case it counts as a loss.
+    #[serde(default)]
+    pub abandoned: bool,
+}
+
+/// A finished game along with everything that happened in it.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct GameRecord {
+    #[serde(flatten)]
+    pub summary: GameSummary,
+    pub log: Vec<Event>,
+}
+
+/// The statistics of a player over all the games they finished.
+#[derive(Debug, Clone, PartialEq, Serialize, JsonSchema)]
+pub struct PlayerStats {
+    pub player: Uuid,
+    pub games_played: usize,
+    pub wins: usize,
+    /// The share of games won, from 0 to 1.
+    pub win_rate: f64,
+    /// The average number of cards left unplayed.
+    pub average_remaining_cards: f64,
+    /// The lowest number of cards left unplayed, `None` if no game was played.
+    pub best_score: Option<usize>,
+}
+
+/// The finished games, kept in memory and appended to a file if opened from a directory.
+#[derive(Debug, Default)]
+pub struct History {
+    games: Vec<GameRecord>,
+    file: Option<File>,
+}
+
+impl History {
+    /// Loads the games recorded in the given directory, and records the next ones there.
+    pub fn open(dir: &Path) -> io::Result<Self> {
+        fs::create_dir_all(dir)?;
+        let path = dir.join(HISTORY_FILE);
+        let mut games = Vec::new();
+        if path.exists() {
+            for (number, line) in fs::read_to_string(&path)?.lines().enumerate() {
+                match serde_json::from_str(line) {
+                    Ok(game) => games.push(game),
+                    Err(err) => warn!(path = %path.display(), line = number + 1, %err, "Skipping unreadable game record"),
+                }
+            }
+        }
+
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self { games, file: Some(file) })
+    }
+
+    /// Records a finished game.
+    pub fn record(&mut self, game: GameRecord) -> io::Result<()> {
+        if let Some(file) = &mut self.file {
+            let mut line = serde_json::to_vec(&game)?;
+            line.push(b'\n');
+            file.write_all(&line)?;
+        }
+
+        self.games.push(game);
+        Ok(())
+    }
+
+    /// Returns the game with the given id.
+    pub fn game(&self, id: &Uuid) -> Option<&GameRecord> {
+        self.games.iter().find(|game| game.summary.id == *id)
+    }
+
+    /// Returns the games the given player took part in, or all games if `None`, oldest first.
+    pub fn games<'a>(&'a self, player: Option<&'a Uuid>) -> impl Iterator<Item = &'a GameSummary> {
+        self.games.iter()
+            .map(|game| &game.summary)
+            .filter(move |game| player.is_none_or(|player| game.players.contains(player)))
+    }
+
+    /// Returns the statistics of the given player.
+    pub fn stats(&self, player: Uuid) -> PlayerStats {
+        let games = self.games(Some(&player)).collect::<Vec<_>>();
+        let wins = games.iter().filter(|game| game.outcome == Outcome::Win).count();
+        let remaining = games.iter().map(|game| game.score).sum::<usize>();
+        let ratio = |value: usize| if games.is_empty() { 0.0 } else { value as f64 / games.len() as f64 };
+
+        PlayerStats {
+            player,
+            games_played: games.len(),
+            wins,
+            win_rate: ratio(wins),
+            average_remaining_cards: ratio(remaining),
+            best_score: games.iter().map(|game| game.score).min(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+    use the_game_core::game::Outcome;
+    use the_game_core::variant::Variant;
+    use crate::history::{GameRecord, GameSummary, History};
+
+    fn record(players: Vec<Uuid>, outcome: Outcome, score: usize) -> GameRecord {
+        let summary = GameSummary {
+            id: Uuid::new_v4(),
+            room_id: Uuid::new_v4(),
+            variant: Variant::Standard,
+            seed: 7,
+            names: players.iter().map(|_| "Alice".to_string()).collect(),
+            players,
+            outcome,
+            score,
+            started_at: 0,
+            duration: 60,
+            abandoned: false,
+        };
+
+        GameRecord { summary, log: Vec::new() }
+    }
+
+    #[test]
+    fn test_stats() {
+        let dir = std::env::temp_dir().join(format!("thegame-{}", Uuid::new_v4()));
+        let (alice, bob) = (Uuid::new_v4(), Uuid::new_v4());
+        let mut history = History::open(&dir).unwrap();
+        history.record(record(vec![alice, bob], Outcome::Win, 0)).unwrap();
+        history.record(record(vec![alice], Outcome::Loss, 10)).unwrap();
+
+        let history = History::open(&dir).unwrap();
+        let stats = history.stats(alice);
+        assert_eq!(stats.games_played, 2);
+        assert_eq!(stats.win_rate, 0.5);
+        assert_eq!(stats.average_remaining_cards, 5.0);
+        assert_eq!(stats.best_score, Some(0));
+        assert_eq!(history.games(Some(&bob)).count(), 1);
+        assert_eq!(history.stats(Uuid::new_v4()).best_score, None);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,89 @@
+//! Player identities, proven by tokens signed with the secret of the server.
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use hmac::{Hmac, KeyInit, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use uuid::Uuid;
+use crate::error::GameError;
+
+/// The longest display name, in characters.
+pub const MAX_NAME_LENGTH: usize = 32;
+
+/// A registered player.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Identity {
+    pub id: Uuid,
+    pub name: String,
+}
+
+impl Identity {
+    /// Signs this identity into a token, `<payload>.<signature>` encoded as URL-safe base64.
+    pub fn sign(&self, secret: &str) -> String {
+        let payload = URL_SAFE_NO_PAD.encode(serde_json::to_vec(self).unwrap());
+        let signature = URL_SAFE_NO_PAD.encode(mac(secret, &payload).finalize().into_bytes());
+        format!("{}.{}", payload, signature)
+    }
+
+    /// Returns the identity a token was signed for, if it was signed with the given secret.
+    pub fn verify(secret: &str, token: &str) -> Option<Self> {
+        let (payload, signature) = token.split_once('.')?;
+        let signature = URL_SAFE_NO_PAD.decode(signature).ok()?;
+        mac(secret, payload).verify_slice(&signature).ok()?;
+        serde_json::from_slice(&URL_SAFE_NO_PAD.decode(payload).ok()?).ok()
+    }
+}
+
+/// Checks that a display name is presentable, returning it without surrounding whitespace.
+pub fn validate_name(name: &str) -> Result<&str, GameError> {
+    let name = name.trim();
+    if name.is_empty() || name.chars().count() > MAX_NAME_LENGTH || name.chars().any(char::is_control) {
+        return Err(GameError::InvalidName);
+    }
+
+    Ok(name)
+}
+
+/// The name shown for a player who has not registered.
+pub fn guest_name(id: &Uuid) -> String {
+    format!("Guest-{}", &id.simple().to_string()[..4])
+}
+
+/// The name shown for a computer player.
+pub fn bot_name(id: &Uuid) -> String {
+    format!("Bot-{}", &id.simple().to_string()[..4])
+}
+
+fn mac(secret: &str, payload: &str) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size");
+    mac.update(payload.as_bytes());
+    mac
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+    use crate::identity::{validate_name, Identity};
+
+    #[test]
+    fn test_token() {
+        let identity = Identity { id: Uuid::new_v4(), name: "Alice".to_string() };
+        let token = identity.sign("secret");
+        assert_eq!(Identity::verify("secret", &token), Some(identity));
+        assert_eq!(Identity::verify("other secret", &token), None);
+
+        let (_, signature) = token.split_once('.').unwrap();
+        let forged = Identity { id: Uuid::new_v4(), name: "Alice".to_string() }.sign("secret");
+        let (payload, _) = forged.split_once('.').unwrap();
+        assert_eq!(Identity::verify("secret", &format!("{}.{}", payload, signature)), None);
+        assert_eq!(Identity::verify("secret", "garbage"), None);
+    }
+
+    #[test]
+    fn test_validate_name() {
+        assert_eq!(validate_name("  Alice "), Ok("Alice"));
+        assert!(validate_name(" ").is_err());
+        assert!(validate_name("Al\nice").is_err());
+        assert!(validate_name(&"a".repeat(33)).is_err());
+    }
+}
@@ -0,0 +1,20 @@
+pub mod api;
+pub mod bots;
+pub mod clock;
+pub mod codec;
+pub mod config;
+pub mod error;
+pub mod handlers;
+pub mod history;
+pub mod identity;
+pub mod logging;
+pub mod messages;
+pub mod metrics;
+pub mod protocol;
+pub mod rate_limit;
+pub mod room;
+pub mod schema;
+pub mod server;
+pub mod session;
+pub mod shutdown;
+pub mod storage;
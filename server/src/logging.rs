@@ -0,0 +1,17 @@
+//! Structured logging of the server.
+use tracing_subscriber::EnvFilter;
+use crate::config::{LogFormat, ServerConfig};
+
+/// Installs the global logger described by the settings.
+///
+/// Returns `Err` if the log level can't be parsed.
+pub fn init(config: &ServerConfig) -> Result<(), String> {
+    let filter = EnvFilter::try_new(&config.log_level).map_err(|err| format!("Invalid log level: {}", err))?;
+    let builder = tracing_subscriber::fmt().with_env_filter(filter);
+    match config.log_format {
+        LogFormat::Plain => builder.init(),
+        LogFormat::Json => builder.json().flatten_event(true).with_current_span(true).init(),
+    }
+
+    Ok(())
+}
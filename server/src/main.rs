@@ -1,23 +1,73 @@
+use std::sync::Arc;
 use actix::{Actor, Addr};
-use actix_web::{App, Error, HttpRequest, HttpResponse, HttpServer, web::{Data, Payload, ServiceConfig, get}};
-use actix_web::web::route;
+use actix_web::{App, Error, HttpRequest, HttpResponse, HttpServer, web::{Data, Payload, Query, get}};
 use actix_web_actors::ws;
+use clap::error::ErrorKind;
+use clap::{CommandFactory, Parser};
+use serde::Deserialize;
 use uuid::Uuid;
+use the_game_server::codec::Codec;
+use the_game_server::config::{Args, ServerConfig};
+use the_game_server::history::History;
+use the_game_server::storage::FileStorage;
+use the_game_server::{api, logging, server, session, shutdown};
 
-mod session;
-mod server;
-mod messages;
-mod room;
-mod protocol;
-mod handlers;
+/// Query parameters of the websocket endpoint.
+#[derive(Deserialize)]
+struct ConnectParams {
+    /// The encoding of the frames, JSON by default.
+    #[serde(default)]
+    codec: Codec,
+}
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
-    let server = server::GameServer::default().start();
-    let app = move || App::new().app_data(Data::new(server.clone())).route("/ws", get().to(index));
-    HttpServer::new(app).bind(("127.0.0.1", 8080))?.run().await
+    let config = match ServerConfig::load(Args::parse()) {
+        Ok(config) => Arc::new(config),
+        Err(err) => Args::command().error(ErrorKind::InvalidValue, err).exit(),
+    };
+
+    if let Err(err) = logging::init(&config) {
+        Args::command().error(ErrorKind::InvalidValue, err).exit()
+    }
+
+    tracing::info!(host = config.host, port = config.port, "Starting server");
+    let mut server = server::GameServer::new(config.clone());
+    if let Some(dir) = &config.data_dir {
+        server.restore(Box::new(FileStorage::new(dir)?))?;
+        server.history = History::open(dir)?;
+    }
+
+    let server = server.start();
+    let game_server = server.clone();
+    let data = Data::from(config.clone());
+    let app = move || {
+        App::new()
+            .app_data(Data::new(server.clone()))
+            .app_data(data.clone())
+            .configure(api::configure)
+            .route("/ws", get().to(index))
+    };
+
+    // Signals are handled by our own shutdown sequence.
+    let mut http = HttpServer::new(app).disable_signals().shutdown_timeout(config.shutdown_timeout.as_secs());
+    if let Some(workers) = config.workers {
+        http = http.workers(workers);
+    }
+
+    let http = http.bind((config.host.as_str(), config.port))?.run();
+    actix_web::rt::spawn(shutdown::on_signal(game_server, http.handle(), config.shutdown_timeout));
+    http.await
 }
 
-async fn index(req: HttpRequest, stream: Payload, srv: Data<Addr<server::GameServer>>) -> Result<HttpResponse, Error> {
-    ws::start(session::Session::new(Uuid::nil(), srv.get_ref().clone()), &req, stream)
+async fn index(
+    req: HttpRequest,
+    stream: Payload,
+    params: Query<ConnectParams>,
+    srv: Data<Addr<server::GameServer>>,
+    config: Data<ServerConfig>,
+) -> Result<HttpResponse, Error> {
+    let max_frame_size = config.max_frame_size;
+    let session = session::Session::new(Uuid::new_v4(), srv.get_ref().clone(), params.codec, config.into_inner());
+    ws::WsResponseBuilder::new(session, &req, stream).frame_size(max_frame_size).start()
 }
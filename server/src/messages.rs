@@ -1,44 +1,221 @@
 use actix::Message;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use strum::VariantNames;
 use uuid::Uuid;
+use the_game_core::game::Outcome;
+use the_game_core::strategy::BuiltinStrategy;
+use crate::clock::{TimeoutPolicy, TurnClock};
+use crate::codec::Codec;
 
 /// A message from the client, transported over the websocket
-/// connection.
-#[derive(Message, Debug, Deserialize)]
-#[serde(tag = "type", content = "params", rename_all = "snake_case")]
+/// connection as the `method` and `params` of a JSON-RPC request.
+#[derive(Message, Debug, Deserialize, JsonSchema, VariantNames)]
+#[serde(tag = "method", content = "params", rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
 #[rtype(result = "()")]
 pub enum MessageFromClient {
+    /// A client introduces itself, it must be the first request of the session.
+    Hello(HelloMessage),
     /// A client joins the room with the given id.
-    JoinRoom(Uuid),
+    JoinRoom(RoomMessage),
     /// A client wants to leave its current game room.
-    LeaveRoom(Uuid),
+    LeaveRoom(RoomMessage),
     /// A client creates a new room.
     CreateRoom,
     /// A client starts the game.
-    StartGame(Uuid),
+    StartGame(RoomMessage),
     /// A client discards a card.
     DiscardCard(DiscardCardMessage),
     /// A client ends the turn.
-    EndTurn(Uuid),
+    EndTurn(RoomMessage),
+    /// A client takes back the seats of a previous session.
+    Reconnect(ReconnectMessage),
+    /// A client lists the finished games of a player.
+    ListGames(PlayerMessage),
+    /// A client asks for a finished game and everything that happened in it.
+    GetGame(GameMessage),
+    /// A client asks for a turn by turn review of a finished game.
+    AnalyzeGame(GameMessage),
+    /// A client asks for the statistics of a player.
+    PlayerStats(PlayerMessage),
+    /// A client registers a display name, in exchange for a token proving its identity.
+    Register(RegisterMessage),
+    /// A client limits how long each turn of the game in a room may last.
+    SetTurnClock(TurnClockMessage),
+    /// A client seats a computer player in a room.
+    AddBot(AddBotMessage),
+    /// A client lets the players of a room ask for advice, or stops them from doing so.
+    SetAdvice(AdviceMessage),
+    /// A client asks for the moves it could make on its turn, the best first.
+    RequestAdvice(RoomMessage),
 }
 
-#[derive(Debug, Deserialize)]
+impl MessageFromClient {
+    /// The names of all methods a client may call.
+    pub const METHODS: &'static [&'static str] = Self::VARIANTS;
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct HelloMessage {
+    /// The version of the protocol the client speaks.
+    pub protocol_version: u32,
+    /// The name and version of the client, for diagnostics.
+    #[serde(default)]
+    pub client: Option<String>,
+    /// The encoding to switch to once the handshake is complete.
+    #[serde(default)]
+    pub codec: Option<Codec>,
+    /// The token given by `register`, restoring the identity of the player.
+    #[serde(default)]
+    pub player_token: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct RegisterMessage {
+    /// The display name, from 1 to 32 printable characters.
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ReconnectMessage {
+    /// The reconnect token given to the previous session by `hello`.
+    pub token: String,
+}
+
+#[derive(Debug, Default, Deserialize, JsonSchema)]
+pub struct PlayerMessage {
+    /// The player, the session itself if omitted.
+    #[serde(default)]
+    pub player: Option<Uuid>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct GameMessage {
+    pub game_id: Uuid,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct RoomMessage {
+    pub room_id: Uuid,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct TurnClockMessage {
+    pub room_id: Uuid,
+    /// The clock of the room, turns are not limited if omitted.
+    #[serde(default)]
+    pub clock: Option<TurnClock>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct AddBotMessage {
+    pub room_id: Uuid,
+    /// How the bot plays, `random`, `smallest_gap`, `backwards_trick` or `ismcts`.
+    #[schemars(with = "String")]
+    pub strategy: BuiltinStrategy,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct AdviceMessage {
+    pub room_id: Uuid,
+    /// Whether the players may ask for advice on their turns.
+    pub enabled: bool,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
 pub struct DiscardCardMessage {
     pub card: u8,
     pub position: usize,
     pub room_id: Uuid,
 }
 
+/// The result of the `hello` request.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct HelloResponse {
+    /// The version of the protocol used for the rest of the session.
+    pub protocol_version: u32,
+    /// All protocol versions the server speaks.
+    pub supported_versions: Vec<u32>,
+    /// Optional protocol features the server supports.
+    pub capabilities: Vec<String>,
+    /// The encoding used for the rest of the session.
+    pub codec: Codec,
+    /// A secret letting a later session take back the seats of this one with `reconnect`.
+    pub reconnect_token: String,
+    /// The id of the player, which is the one of the registered player if `player_token` was given.
+    pub player_id: Uuid,
+    /// The display name of the player.
+    pub name: String,
+}
+
+/// The result of the `register` request.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct RegisterResponse {
+    pub player_id: Uuid,
+    pub name: String,
+    /// The token to pass to `hello` in later sessions to restore this identity.
+    pub player_token: String,
+}
+
+/// The result of the `reconnect` request.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ReconnectResponse {
+    /// The rooms in which the session took back its seat.
+    pub rooms: Vec<Uuid>,
+}
+
+/// The result of the `request_advice` request.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct AdviceResponse {
+    /// Every legal move, the best first.
+    pub suggestions: Vec<Suggestion>,
+}
+
+/// A move suggested to a player, as the parameters of `discard_card`.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Suggestion {
+    /// The card to discard, or `None` to end the turn.
+    pub card: Option<u8>,
+    /// The pile to discard the card onto, or `None` to end the turn.
+    pub position: Option<usize>,
+    /// Why the move is suggested, naming the piles from 1 to 4.
+    pub explanation: String,
+}
+
 /// A message sent from the game to the client handler threads
 /// which more directly interact with the players.
-#[derive(Serialize, Deserialize, Clone)]
-#[serde(tag = "type", content = "payload", rename_all = "snake_case")]
+/// It is transported as a JSON-RPC notification.
+#[derive(Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(tag = "method", content = "params", rename_all = "snake_case")]
 pub enum MessageToClient {
     /// When cards are dealt.
-    DealtHand(Vec<u8>),
+    DealtHand { cards: Vec<u8> },
     /// When someone has discarded their card.
-    DiscardOne(u8, usize),
+    DiscardOne { card: u8, position: usize },
     /// When someone have ended their turn
     /// and the next player has been instructed to take theirs.
-    EndTurn(usize),
+    EndTurn { player: usize, name: String },
+    /// When a player starts their turn, with how long it may last in seconds if it is limited.
+    TurnStarted { turn: usize, player: usize, name: String, time_limit: Option<u64> },
+    /// When the player who has the turn has only the given number of seconds left.
+    TurnClockWarning { player: usize, seconds_left: u64 },
+    /// When the player who has the turn ran out of time, and the given policy is applied.
+    TurnTimedOut { player: usize, policy: TimeoutPolicy },
+    /// When the server is about to close every session, in the given number of seconds.
+    ServerShuttingDown { seconds: u64 },
+    /// When the game is over, with the number of cards left unplayed.
+    GameOver { outcome: Outcome, score: usize },
+    /// When a room is closed by the server, ending its game if it was still running.
+    RoomClosed { room_id: Uuid, reason: ClosedReason },
+}
+
+/// Why the server closed a room.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ClosedReason {
+    /// Every player left the room.
+    Empty,
+    /// Nothing happened in the room for too long.
+    Idle,
 }
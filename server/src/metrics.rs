@@ -0,0 +1,192 @@
+//! Counters of the game server, exposed in the Prometheus text format.
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+use std::time::Duration;
+use the_game_core::game::{GameStatus, Outcome};
+use crate::error::GameError;
+use crate::room::Room;
+
+/// How often the latency of the game server mailbox is measured.
+pub const MAILBOX_PROBE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Upper bounds of the final score buckets.
+const SCORE_BUCKETS: &[f64] = &[0.0, 1.0, 2.0, 5.0, 10.0, 20.0, 50.0, 98.0];
+
+/// Upper bounds of the mailbox latency buckets, in seconds.
+const LATENCY_BUCKETS: &[f64] = &[0.0001, 0.0005, 0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0];
+
+/// Counters updated by the message handlers of the game server.
+#[derive(Debug)]
+pub struct Metrics {
+    /// The number of games started.
+    pub games_started: u64,
+    /// The number of games finished, by outcome.
+    pub games_finished: HashMap<Outcome, u64>,
+    /// The number of cards left when games finished.
+    pub scores: Histogram,
+    /// The number of messages handled, by type.
+    pub messages: BTreeMap<&'static str, u64>,
+    /// The number of requests refused, by error kind.
+    pub rejections: BTreeMap<&'static str, u64>,
+    /// How long messages wait in the mailbox of the game server.
+    pub mailbox_latency: Histogram,
+    /// The number of rooms closed by the server, by reason.
+    pub rooms_closed: BTreeMap<&'static str, u64>,
+}
+
+/// Samples counted into cumulative buckets.
+#[derive(Debug)]
+pub struct Histogram {
+    bounds: &'static [f64],
+    buckets: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+impl Metrics {
+    /// Counts a handled message.
+    pub fn message(&mut self, name: &'static str) {
+        *self.messages.entry(name).or_default() += 1;
+    }
+
+    /// Counts a handled message and, if it was refused, the reason why.
+    pub fn observe<T>(&mut self, name: &'static str, result: &Result<T, GameError>) {
+        self.message(name);
+        if let Err(err) = result {
+            *self.rejections.entry(err.kind()).or_default() += 1;
+        }
+    }
+
+    /// Counts a finished game.
+    pub fn game_over(&mut self, outcome: Outcome, score: usize) {
+        *self.games_finished.entry(outcome).or_default() += 1;
+        self.scores.observe(score as f64);
+    }
+
+    /// Renders the metrics along with the current state of the server.
+    pub fn render<'a>(&self, sessions: usize, rooms: impl Iterator<Item = &'a Room>) -> String {
+        let mut by_status = BTreeMap::from([("created", 0), ("playing", 0), ("won", 0), ("lost", 0)]);
+        for room in rooms {
+            let status = match room.game.status {
+                GameStatus::Created => "created",
+                GameStatus::Playing => "playing",
+                GameStatus::GameOver(Outcome::Win) => "won",
+                GameStatus::GameOver(Outcome::Loss) => "lost",
+            };
+            *by_status.entry(status).or_default() += 1;
+        }
+
+        let mut out = String::new();
+        header(&mut out, "thegame_sessions", "gauge", "Connected websocket sessions.");
+        writeln!(out, "thegame_sessions {}", sessions).unwrap();
+
+        header(&mut out, "thegame_rooms", "gauge", "Rooms by the status of their game.");
+        for (status, count) in by_status {
+            writeln!(out, "thegame_rooms{{status=\"{}\"}} {}", status, count).unwrap();
+        }
+
+        header(&mut out, "thegame_games_started_total", "counter", "Games started.");
+        writeln!(out, "thegame_games_started_total {}", self.games_started).unwrap();
+
+        header(&mut out, "thegame_games_finished_total", "counter", "Games finished, by outcome.");
+        for (outcome, label) in [(Outcome::Win, "win"), (Outcome::Loss, "loss")] {
+            let count = self.games_finished.get(&outcome).copied().unwrap_or_default();
+            writeln!(out, "thegame_games_finished_total{{outcome=\"{}\"}} {}", label, count).unwrap();
+        }
+
+        header(&mut out, "thegame_final_score", "histogram", "Cards left when a game finished.");
+        self.scores.render(&mut out, "thegame_final_score");
+
+        header(&mut out, "thegame_messages_total", "counter", "Messages handled by the game server, by type.");
+        for (name, count) in &self.messages {
+            writeln!(out, "thegame_messages_total{{type=\"{}\"}} {}", name, count).unwrap();
+        }
+
+        header(&mut out, "thegame_rejections_total", "counter", "Requests refused by the game server, by error kind.");
+        for (kind, count) in &self.rejections {
+            writeln!(out, "thegame_rejections_total{{kind=\"{}\"}} {}", kind, count).unwrap();
+        }
+
+        header(&mut out, "thegame_rooms_closed_total", "counter", "Rooms closed by the server, by reason.");
+        for (reason, count) in &self.rooms_closed {
+            writeln!(out, "thegame_rooms_closed_total{{reason=\"{}\"}} {}", reason, count).unwrap();
+        }
+
+        header(&mut out, "thegame_mailbox_latency_seconds", "histogram", "Time messages wait in the game server mailbox.");
+        self.mailbox_latency.render(&mut out, "thegame_mailbox_latency_seconds");
+        out
+    }
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self {
+            games_started: 0,
+            games_finished: HashMap::new(),
+            scores: Histogram::new(SCORE_BUCKETS),
+            messages: BTreeMap::new(),
+            rejections: BTreeMap::new(),
+            mailbox_latency: Histogram::new(LATENCY_BUCKETS),
+            rooms_closed: BTreeMap::new(),
+        }
+    }
+}
+
+impl Histogram {
+    /// Returns an empty histogram with the given bucket upper bounds.
+    pub fn new(bounds: &'static [f64]) -> Self {
+        Self { bounds, buckets: vec![0; bounds.len()], sum: 0.0, count: 0 }
+    }
+
+    /// Adds a sample.
+    pub fn observe(&mut self, value: f64) {
+        for (bound, bucket) in self.bounds.iter().zip(&mut self.buckets) {
+            if value <= *bound {
+                *bucket += 1;
+            }
+        }
+
+        self.sum += value;
+        self.count += 1;
+    }
+
+    fn render(&self, out: &mut String, name: &str) {
+        for (bound, bucket) in self.bounds.iter().zip(&self.buckets) {
+            writeln!(out, "{}_bucket{{le=\"{}\"}} {}", name, bound, bucket).unwrap();
+        }
+
+        writeln!(out, "{}_bucket{{le=\"+Inf\"}} {}", name, self.count).unwrap();
+        writeln!(out, "{}_sum {}", name, self.sum).unwrap();
+        writeln!(out, "{}_count {}", name, self.count).unwrap();
+    }
+}
+
+fn header(out: &mut String, name: &str, kind: &str, help: &str) {
+    writeln!(out, "# HELP {} {}", name, help).unwrap();
+    writeln!(out, "# TYPE {} {}", name, kind).unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use the_game_core::game::Outcome;
+    use crate::error::GameError;
+    use crate::metrics::Metrics;
+    use crate::room::Room;
+
+    #[test]
+    fn test_render() {
+        let mut metrics = Metrics::default();
+        metrics.observe("end_turn", &Err::<(), _>(GameError::NotYourTurn));
+        metrics.observe("end_turn", &Ok(()));
+        metrics.game_over(Outcome::Loss, 7);
+
+        let text = metrics.render(2, [Room::new()].iter());
+        assert!(text.contains("thegame_sessions 2\n"));
+        assert!(text.contains("thegame_rooms{status=\"created\"} 1\n"));
+        assert!(text.contains("thegame_games_finished_total{outcome=\"loss\"} 1\n"));
+        assert!(text.contains("thegame_final_score_bucket{le=\"5\"} 0\n"));
+        assert!(text.contains("thegame_final_score_bucket{le=\"10\"} 1\n"));
+        assert!(text.contains("thegame_messages_total{type=\"end_turn\"} 2\n"));
+        assert!(text.contains("thegame_rejections_total{kind=\"not_your_turn\"} 1\n"));
+    }
+}
@@ -1,7 +1,31 @@
-use serde::{Deserialize, Serialize};
+//! A JSON-RPC 2.0 transport layer.
+//!
+//! See <https://www.jsonrpc.org/specification>.
+use std::borrow::Cow;
+use schemars::{json_schema, JsonSchema, Schema, SchemaGenerator};
+use serde::{Deserialize, Serialize, Serializer};
+use serde_json::Value;
+use crate::codec::Codec;
+use crate::error::GameError;
 use crate::messages;
 
-#[derive(Clone, Debug, Deserialize, Serialize, Eq, PartialEq, Ord, PartialOrd)]
+/// The version of the game protocol spoken by this server.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// All versions of the game protocol this server can speak.
+pub const SUPPORTED_VERSIONS: &[u32] = &[PROTOCOL_VERSION];
+
+/// Optional protocol features announced to the client during the handshake.
+pub const CAPABILITIES: &[&str] = &["batch", "notifications", "msgpack"];
+
+/// The JSON-RPC protocol version, always serialized as `"2.0"`.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, Eq, PartialEq, JsonSchema)]
+pub enum Version {
+    #[serde(rename = "2.0")]
+    V2,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, Eq, PartialEq, Ord, PartialOrd, JsonSchema)]
 #[serde(untagged)]
 pub enum Id {
     /// Numeric ID.
@@ -10,49 +34,334 @@ pub enum Id {
     String(String),
 }
 
-#[derive(Deserialize)]
+/// A frame received from the client.
+#[derive(Debug)]
+pub enum Payload {
+    /// A single request or notification.
+    Single(Value),
+    /// A batch of requests and notifications.
+    Batch(Vec<Value>),
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
 pub struct Request {
-    /// An identifier established by the client.
-    pub id: Id,
+    /// The version of the protocol, must be exactly `"2.0"`.
+    pub jsonrpc: Version,
+    /// The name of the method to be invoked.
+    pub method: String,
     /// A structured value that holds the parameter values
     /// to be used during the invocation of the method.
-    pub payload: messages::MessageFromClient,
+    #[serde(default)]
+    pub params: Option<Value>,
+    /// An identifier established by the client.
+    /// A request without an id is a notification and is never answered.
+    #[serde(default)]
+    pub id: Option<Id>,
 }
 
-#[derive(Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Response {
+    pub jsonrpc: Version,
+    /// Either the `result` or the `error` member.
+    #[serde(flatten)]
+    pub outcome: Outcome,
+    /// Correlation id.
+    /// It must be the same as the value of the id member in the request object,
+    /// or `null` if the id could not be detected.
+    pub id: Option<Id>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
-pub enum Response<T> where T: Serialize {
+pub enum Outcome {
     /// Success response.
-    Success(Result<T>),
+    Result(Value),
     /// Failure response.
-    Failure(Result<T>),
+    Error(Error),
 }
 
-#[derive(Serialize)]
-pub struct Result<T> where T: Serialize {
-    /// Correlation id.
-    /// It must be the same as the value of the id member in the request object.
-    pub id: Id,
-    /// Execution payload.
-    pub payload: T,
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Error {
+    /// A number that indicates the error type that occurred.
+    pub code: ErrorCode,
+    /// A short description of the error.
+    pub message: String,
+    /// Additional information about the error.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+}
+
+/// Error codes of the protocol.
+/// Codes from -32768 to -32000 are reserved by the specification,
+/// the game-specific ones are allocated downwards from -32000.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCode {
+    /// Invalid JSON was received by the server.
+    ParseError,
+    /// The JSON sent is not a valid request object.
+    InvalidRequest,
+    /// The method does not exist.
+    MethodNotFound,
+    /// Invalid method parameters.
+    InvalidParams,
+    /// Internal server error.
+    InternalError,
+    /// The game rules do not allow the action.
+    ActionRejected,
+    /// There is no room with the given id.
+    RoomNotFound,
+    /// Only the room owner may perform the action.
+    NotRoomOwner,
+    /// The session is not a member of the room.
+    NotInRoom,
+    /// Someone else has the turn.
+    NotYourTurn,
+    /// The session has not completed the `hello` handshake yet.
+    HandshakeRequired,
+    /// The server does not speak the requested protocol version.
+    UnsupportedVersion,
+    /// The server can't host any more rooms.
+    TooManyRooms,
+    /// The room has no free seats.
+    RoomFull,
+    /// The session sent this method too often.
+    RateLimited,
+    /// The frame is larger than the server accepts.
+    FrameTooLarge,
+    /// The reconnect token does not belong to any session.
+    InvalidToken,
+    /// There is no finished game with the given id.
+    GameNotFound,
+    /// The display name is empty, too long or not printable.
+    InvalidName,
+    /// The server is shutting down.
+    ShuttingDown,
+    /// Advice is turned off in the room.
+    AdviceDisabled,
+}
+
+/// An event sent by the server that does not expect a response.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Notification<T> where T: Serialize {
+    pub jsonrpc: Version,
+    /// The `method` and `params` members.
+    #[serde(flatten)]
+    pub event: T,
+}
+
+/// Splits a frame into one or more calls.
+///
+/// Returns the response to send back if the frame can't be decoded
+/// or is an empty batch.
+pub fn decode(codec: Codec, data: &[u8]) -> Result<Payload, Response> {
+    match codec.decode::<Value>(data) {
+        Ok(Value::Array(calls)) if calls.is_empty() => {
+            Err(Response::failure(None, Error::new(ErrorCode::InvalidRequest)))
+        }
+        Ok(Value::Array(calls)) => Ok(Payload::Batch(calls)),
+        Ok(call) => Ok(Payload::Single(call)),
+        Err(err) => Err(Response::failure(None, Error::new(ErrorCode::ParseError).with_data(err))),
+    }
+}
+
+impl Request {
+    /// Validates a single call of a frame.
+    ///
+    /// Returns the response to send back if it isn't a valid request object.
+    pub fn from_value(value: Value) -> Result<Self, Response> {
+        let id = value.get("id").cloned().and_then(|id| serde_json::from_value(id).ok());
+        serde_json::from_value(value).map_err(|err| {
+            Response::failure(id, Error::new(ErrorCode::InvalidRequest).with_data(err.to_string()))
+        })
+    }
+
+    /// Returns `true` if the client does not expect a response.
+    pub fn is_notification(&self) -> bool {
+        self.id.is_none()
+    }
+
+    /// Resolves the method and parameters into a message.
+    pub fn into_message(self) -> Result<messages::MessageFromClient, Error> {
+        if !messages::MessageFromClient::METHODS.contains(&self.method.as_str()) {
+            return Err(Error::new(ErrorCode::MethodNotFound).with_data(self.method));
+        }
+
+        let mut call = serde_json::Map::new();
+        call.insert("method".to_string(), Value::String(self.method));
+        let Some(params) = self.params else {
+            // Methods whose parameters are all optional may be called without any.
+            let without_params = serde_json::from_value(Value::Object(call.clone()));
+            call.insert("params".to_string(), Value::Object(serde_json::Map::new()));
+            return without_params
+                .or_else(|_| serde_json::from_value(Value::Object(call)))
+                .map_err(|err| Error::new(ErrorCode::InvalidParams).with_data(err.to_string()));
+        };
+
+        if !params.is_object() && !params.is_array() {
+            return Err(Error::new(ErrorCode::InvalidParams).with_data("params must be an object or an array"));
+        }
+
+        call.insert("params".to_string(), params);
+        serde_json::from_value(Value::Object(call))
+            .map_err(|err| Error::new(ErrorCode::InvalidParams).with_data(err.to_string()))
+    }
 }
 
-impl<T> Response<T> where T: Serialize {
+impl Response {
     /// Creates a success response.
-    pub fn success(id: Id, payload: T) -> Self {
-        Self::Success(Result::new(id, payload))
+    pub fn success<T>(id: Id, result: T) -> Self where T: Serialize {
+        match serde_json::to_value(result) {
+            Ok(result) => Self::new(Some(id), Outcome::Result(result)),
+            Err(err) => Self::failure(Some(id), Error::new(ErrorCode::InternalError).with_data(err.to_string())),
+        }
     }
 
     /// Creates a failure response.
-    pub fn failure(id: Id, payload: T) -> Self {
-        Self::Failure(Result::new(id, payload))
+    pub fn failure(id: Option<Id>, error: Error) -> Self {
+        Self::new(id, Outcome::Error(error))
+    }
+
+    fn new(id: Option<Id>, outcome: Outcome) -> Self {
+        Self { jsonrpc: Version::V2, outcome, id }
+    }
+}
+
+impl Error {
+    /// Creates an error with the default message of the given code.
+    pub fn new(code: ErrorCode) -> Self {
+        Self { code, message: code.message().to_string(), data: None }
+    }
+
+    /// Attaches additional information to the error.
+    pub fn with_data<T>(mut self, data: T) -> Self where T: Into<Value> {
+        self.data = Some(data.into());
+        self
+    }
+}
+
+impl ErrorCode {
+    /// All error codes of the protocol.
+    pub const ALL: &'static [ErrorCode] = &[
+        ErrorCode::ParseError,
+        ErrorCode::InvalidRequest,
+        ErrorCode::MethodNotFound,
+        ErrorCode::InvalidParams,
+        ErrorCode::InternalError,
+        ErrorCode::ActionRejected,
+        ErrorCode::RoomNotFound,
+        ErrorCode::NotRoomOwner,
+        ErrorCode::NotInRoom,
+        ErrorCode::NotYourTurn,
+        ErrorCode::HandshakeRequired,
+        ErrorCode::UnsupportedVersion,
+        ErrorCode::TooManyRooms,
+        ErrorCode::RoomFull,
+        ErrorCode::RateLimited,
+        ErrorCode::FrameTooLarge,
+        ErrorCode::InvalidToken,
+        ErrorCode::GameNotFound,
+        ErrorCode::InvalidName,
+        ErrorCode::ShuttingDown,
+        ErrorCode::AdviceDisabled,
+    ];
+
+    /// Returns the numeric value of this code.
+    pub fn code(&self) -> i64 {
+        match self {
+            ErrorCode::ParseError => -32700,
+            ErrorCode::InvalidRequest => -32600,
+            ErrorCode::MethodNotFound => -32601,
+            ErrorCode::InvalidParams => -32602,
+            ErrorCode::InternalError => -32603,
+            ErrorCode::ActionRejected => -32000,
+            ErrorCode::RoomNotFound => -32001,
+            ErrorCode::NotRoomOwner => -32002,
+            ErrorCode::NotInRoom => -32003,
+            ErrorCode::NotYourTurn => -32004,
+            ErrorCode::HandshakeRequired => -32005,
+            ErrorCode::UnsupportedVersion => -32006,
+            ErrorCode::TooManyRooms => -32007,
+            ErrorCode::RoomFull => -32008,
+            ErrorCode::RateLimited => -32009,
+            ErrorCode::FrameTooLarge => -32010,
+            ErrorCode::InvalidToken => -32011,
+            ErrorCode::GameNotFound => -32012,
+            ErrorCode::InvalidName => -32013,
+            ErrorCode::ShuttingDown => -32014,
+            ErrorCode::AdviceDisabled => -32015,
+        }
+    }
+
+    /// Returns the default message of this code.
+    pub fn message(&self) -> &'static str {
+        match self {
+            ErrorCode::ParseError => "Parse error",
+            ErrorCode::InvalidRequest => "Invalid Request",
+            ErrorCode::MethodNotFound => "Method not found",
+            ErrorCode::InvalidParams => "Invalid params",
+            ErrorCode::InternalError => "Internal error",
+            ErrorCode::ActionRejected => "Action rejected",
+            ErrorCode::RoomNotFound => "Room not found",
+            ErrorCode::NotRoomOwner => "Not the room owner",
+            ErrorCode::NotInRoom => "Not in the room",
+            ErrorCode::NotYourTurn => "Not your turn",
+            ErrorCode::HandshakeRequired => "Handshake required",
+            ErrorCode::UnsupportedVersion => "Unsupported protocol version",
+            ErrorCode::TooManyRooms => "Too many rooms",
+            ErrorCode::RoomFull => "Room full",
+            ErrorCode::RateLimited => "Rate limited",
+            ErrorCode::FrameTooLarge => "Frame too large",
+            ErrorCode::InvalidToken => "Invalid token",
+            ErrorCode::GameNotFound => "Game not found",
+            ErrorCode::InvalidName => "Invalid name",
+            ErrorCode::ShuttingDown => "Shutting down",
+            ErrorCode::AdviceDisabled => "Advice disabled",
+        }
+    }
+}
+
+impl Serialize for ErrorCode {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        serializer.serialize_i64(self.code())
+    }
+}
+
+impl JsonSchema for ErrorCode {
+    fn schema_name() -> Cow<'static, str> {
+        "ErrorCode".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        let codes = ErrorCode::ALL.iter().map(|code| code.code()).collect::<Vec<_>>();
+        json_schema!({ "type": "integer", "enum": codes })
+    }
+}
+
+impl From<GameError> for Error {
+    fn from(err: GameError) -> Self {
+        let code = match err {
+            GameError::RoomNotFound => ErrorCode::RoomNotFound,
+            GameError::NotRoomOwner => ErrorCode::NotRoomOwner,
+            GameError::NotInRoom => ErrorCode::NotInRoom,
+            GameError::NotYourTurn => ErrorCode::NotYourTurn,
+            GameError::TooManyRooms => ErrorCode::TooManyRooms,
+            GameError::RoomFull => ErrorCode::RoomFull,
+            GameError::InvalidToken => ErrorCode::InvalidToken,
+            GameError::GameNotFound => ErrorCode::GameNotFound,
+            GameError::InvalidName => ErrorCode::InvalidName,
+            GameError::ShuttingDown => ErrorCode::ShuttingDown,
+            GameError::AdviceDisabled => ErrorCode::AdviceDisabled,
+            GameError::Rejected(_) => ErrorCode::ActionRejected,
+        };
+
+        Self { code, message: err.to_string(), data: None }
     }
 }
 
-impl<T: Serialize> Result<T> where T: Serialize {
-    /// Creates an execution result.
-    pub fn new(id: Id, payload: T) -> Self {
-        Self { id, payload }
+impl<T> Notification<T> where T: Serialize {
+    /// Wraps an event into a notification.
+    pub fn new(event: T) -> Self {
+        Self { jsonrpc: Version::V2, event }
     }
 }
 
@@ -73,3 +382,61 @@ impl From<String> for Id {
         Id::String(s)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use crate::codec::Codec;
+    use crate::messages::MessageFromClient;
+    use crate::protocol::{decode, ErrorCode, Payload, Request};
+
+    fn error_code(value: serde_json::Value) -> i64 {
+        value["error"]["code"].as_i64().unwrap()
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let response = decode(Codec::Json, b"{\"jsonrpc\": \"2.0\", \"method\"").unwrap_err();
+        let response = serde_json::to_value(response).unwrap();
+        assert_eq!(error_code(response.clone()), -32700);
+        assert!(response["id"].is_null());
+    }
+
+    #[test]
+    fn test_empty_batch() {
+        let response = serde_json::to_value(decode(Codec::Json, b"[]").unwrap_err()).unwrap();
+        assert_eq!(error_code(response), -32600);
+        assert!(matches!(decode(Codec::Json, b"[1, 2]"), Ok(Payload::Batch(calls)) if calls.len() == 2));
+    }
+
+    #[test]
+    fn test_invalid_request() {
+        let response = Request::from_value(json!({"jsonrpc": "1.0", "method": "create_room", "id": 7})).unwrap_err();
+        let response = serde_json::to_value(response).unwrap();
+        assert_eq!(error_code(response.clone()), -32600);
+        assert_eq!(response["id"], 7);
+    }
+
+    #[test]
+    fn test_method_not_found() {
+        let request = Request::from_value(json!({"jsonrpc": "2.0", "method": "fly", "id": 1})).unwrap();
+        assert_eq!(request.into_message().unwrap_err().code, ErrorCode::MethodNotFound);
+    }
+
+    #[test]
+    fn test_into_message() {
+        let request = Request::from_value(json!({"jsonrpc": "2.0", "method": "create_room"})).unwrap();
+        assert!(request.is_notification());
+        assert!(matches!(request.into_message(), Ok(MessageFromClient::CreateRoom)));
+
+        let params = json!({"room_id": "67e55044-10b1-426f-9247-bb680e5fe0c8"});
+        let request = Request::from_value(json!({"jsonrpc": "2.0", "method": "start_game", "params": params, "id": "a"})).unwrap();
+        assert!(matches!(request.into_message(), Ok(MessageFromClient::StartGame(_))));
+
+        let request = Request::from_value(json!({"jsonrpc": "2.0", "method": "start_game", "params": {}, "id": 2})).unwrap();
+        assert_eq!(request.into_message().unwrap_err().code, ErrorCode::InvalidParams);
+
+        let request = Request::from_value(json!({"jsonrpc": "2.0", "method": "player_stats", "id": 3})).unwrap();
+        assert!(matches!(request.into_message(), Ok(MessageFromClient::PlayerStats(_))));
+    }
+}
@@ -0,0 +1,77 @@
+//! Per-session limits on how often each method may be called.
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use crate::config::{RateLimit, ServerConfig};
+
+/// A bucket of tokens, one of which is taken by each call.
+#[derive(Debug)]
+struct TokenBucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// The token buckets of a session, one per method.
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    buckets: HashMap<String, TokenBucket>,
+}
+
+impl TokenBucket {
+    /// Returns a full bucket.
+    fn new(limit: &RateLimit, now: Instant) -> Self {
+        Self { tokens: limit.burst as f64, updated: now }
+    }
+
+    /// Takes a token, or returns how long until one is available.
+    fn take(&mut self, limit: &RateLimit, now: Instant) -> Result<(), Duration> {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * limit.per_second).min(limit.burst as f64);
+        self.updated = now;
+        if self.tokens < 1.0 {
+            return Err(Duration::from_secs_f64((1.0 - self.tokens) / limit.per_second));
+        }
+
+        self.tokens -= 1.0;
+        Ok(())
+    }
+}
+
+impl RateLimiter {
+    /// Counts a call to the given method.
+    /// Returns how long until the method may be called again if it went over its limit.
+    pub fn check(&mut self, method: &str, config: &ServerConfig, now: Instant) -> Result<(), Duration> {
+        let Some(limit) = config.rate_limit(method) else {
+            return Ok(());
+        };
+
+        self.buckets
+            .entry(method.to_string())
+            .or_insert_with(|| TokenBucket::new(limit, now))
+            .take(limit, now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::time::{Duration, Instant};
+    use crate::config::{RateLimit, ServerConfig};
+    use crate::rate_limit::RateLimiter;
+
+    #[test]
+    fn test_token_bucket() {
+        let limits = BTreeMap::from([("end_turn".to_string(), RateLimit { burst: 2, per_second: 1.0 })]);
+        let config = ServerConfig { rate_limits: limits, ..ServerConfig::default() };
+        let mut limiter = RateLimiter::default();
+        let now = Instant::now();
+
+        assert!(limiter.check("end_turn", &config, now).is_ok());
+        assert!(limiter.check("end_turn", &config, now).is_ok());
+        assert_eq!(limiter.check("end_turn", &config, now), Err(Duration::from_secs(1)));
+        assert!(limiter.check("join_room", &config, now).is_ok());
+
+        let later = now + Duration::from_millis(1500);
+        assert!(limiter.check("end_turn", &config, later).is_ok());
+        assert!(limiter.check("end_turn", &config, later).is_err());
+    }
+}
@@ -1,27 +1,106 @@
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime};
 use linked_hash_set::LinkedHashSet;
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
-use the_game_core::game::Game;
+use crate::clock::TurnClock;
+use the_game_core::card::Card;
+use the_game_core::discard_pile::Direction;
+use the_game_core::game::{Game, GameStatus};
+use the_game_core::strategy::BuiltinStrategy;
+use the_game_core::variant::Variant;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Room {
     pub players: LinkedHashSet<Uuid>,
+    /// The display name of each player.
+    #[serde(default)]
+    pub names: HashMap<Uuid, String>,
+    /// The strategy of each computer player.
+    #[serde(default)]
+    pub bots: HashMap<Uuid, BuiltinStrategy>,
     pub game: Game,
+    /// When the game started.
+    #[serde(default)]
+    pub started_at: Option<SystemTime>,
+    /// When something last happened in the room.
+    #[serde(default = "SystemTime::now")]
+    pub active_at: SystemTime,
+    /// How long each turn may last, turns are not limited if `None`.
+    #[serde(default)]
+    pub turn_clock: Option<TurnClock>,
+    /// When the current turn started.
+    #[serde(default)]
+    pub turn_started_at: Option<SystemTime>,
+    /// Whether the players may ask for advice on their turns.
+    #[serde(default)]
+    pub advice: bool,
 }
 
 impl Room {
     /// Returns new `Room` struct initialized with default values.
     pub fn new() -> Self {
-        Self { players: LinkedHashSet::new(), game: Game::new() }
+        Self::default()
     }
 
-    /// Makes the given player join the room.
-    pub fn join(&mut self, player: Uuid) {
+    /// Returns new `Room` struct whose game is played with the given rules.
+    pub fn with_variant(variant: Variant) -> Self {
+        Self { game: Game::with_variant(variant), ..Self::default() }
+    }
+
+    /// Makes the given player join the room under the given name.
+    /// Players can only join before the game has started.
+    pub fn join(&mut self, player: Uuid, name: String) -> Result<(), &'static str> {
+        if !self.game.is_created() {
+            return Err("Can't join an already running game");
+        }
+
         self.players.insert(player);
+        self.names.insert(player, name);
+        Ok(())
     }
 
     /// Removes the given player from the room.
-    pub fn leave(&mut self, player: &Uuid) {
+    /// Players can't leave while the game is running, as it keeps their seat.
+    pub fn leave(&mut self, player: &Uuid) -> Result<(), &'static str> {
+        if self.game.is_playing() {
+            return Err("Can't leave a running game");
+        }
+
         self.players.remove(player);
+        self.names.remove(player);
+        self.bots.remove(player);
+        Ok(())
+    }
+
+    /// Returns the display name of the given player.
+    pub fn name(&self, player: &Uuid) -> &str {
+        self.names.get(player).map_or("", String::as_str)
+    }
+
+    /// Changes the display name of the given player, in the game as well once it has started.
+    pub fn rename(&mut self, player: &Uuid, name: &str) {
+        if let Some(index) = self.player_index(player) {
+            self.names.insert(*player, name.to_string());
+            if let Some(seat) = self.game.players.get_mut(index) {
+                seat.name = name.to_string();
+            }
+        }
+    }
+
+    /// Gives the seat of a player to another one, keeping the seating order.
+    /// Returns `true` if the player was in this room.
+    pub fn replace_player(&mut self, player: &Uuid, with: Uuid) -> bool {
+        if !self.contains(player) {
+            return false;
+        }
+
+        self.players = self.players.iter().map(|id| if id == player { with } else { *id }).collect();
+        if let Some(name) = self.names.remove(player) {
+            self.names.insert(with, name);
+        }
+
+        true
     }
 
     /// Returns the number of players in this room.
@@ -34,18 +113,156 @@ impl Room {
         self.players.is_empty()
     }
 
+    /// Returns `true` if only computer players are left in this room, if any.
+    pub fn is_abandoned(&self) -> bool {
+        self.players.iter().all(|player| self.bots.contains_key(player))
+    }
+
+    /// Returns the strategy of the player who has the turn, if they are a computer player.
+    pub fn current_bot(&self) -> Option<BuiltinStrategy> {
+        let player = self.players.iter().nth(self.game.current_player_index)?;
+        self.bots.get(player).copied()
+    }
+
     /// Starts a new game of by shuffling the deck of cards and
     /// deals the players opening hand.
     pub fn start_game(&mut self) -> Result<(), &'static str> {
-        for _ in 0..self.players.len() {
-            self.game.add_player();
+        for player in &self.players {
+            self.game.add_player(self.names.get(player).cloned().unwrap_or_default());
+        }
+
+        self.game.start()?;
+        self.started_at = Some(SystemTime::now());
+        Ok(())
+    }
+
+    /// Ends the turn of the current player, recording how long they took.
+    pub fn end_turn(&mut self) -> Result<Vec<Card>, &'static str> {
+        if self.game.can_end_turn() {
+            self.record_time();
+        }
+
+        self.game.end_turn()
+    }
+
+    /// Makes the current player give up the game, recording how long they took.
+    pub fn forfeit(&mut self) -> Result<(), &'static str> {
+        if self.game.is_playing() {
+            self.record_time();
         }
 
-        self.game.start()
+        self.game.forfeit()
+    }
+
+    /// Records how long the current player has been playing their turn.
+    fn record_time(&mut self) {
+        if let Some(started_at) = self.turn_started_at.take() {
+            let (turn, player) = (self.game.turn, self.game.current_player_index);
+            self.game.record_time(turn, player, started_at.elapsed().unwrap_or_default());
+        }
+    }
+
+    /// Returns `true` if nothing happened in this room for longer than the given time.
+    pub fn is_idle(&self, timeout: Duration) -> bool {
+        self.active_at.elapsed().is_ok_and(|idle| idle > timeout)
+    }
+
+    /// Returns `true` if the given player is a member of this room.
+    pub fn contains(&self, player: &Uuid) -> bool {
+        self.players.contains(player)
+    }
+
+    /// Returns the seat of the given player at the table.
+    pub fn player_index(&self, player: &Uuid) -> Option<usize> {
+        self.players.iter().position(|id| id == player)
+    }
+
+    /// Returns `true` if the given player has the turn.
+    pub fn is_current_player(&self, player: &Uuid) -> bool {
+        self.player_index(player) == Some(self.game.current_player_index)
     }
 
     /// Returns the owner of the room.
     pub fn get_owner(&self) -> Option<&Uuid> {
         self.players.front()
     }
+
+    /// Returns the publicly visible state of this room.
+    pub fn view(&self, id: Uuid) -> RoomView {
+        let game = &self.game;
+        let piles = game.board().discard_piles().iter()
+            .map(|pile| PileView { top: pile.peek_top_card().0, direction: pile.direction() })
+            .collect();
+
+        RoomView {
+            id,
+            owner: self.get_owner().copied(),
+            players: self.players.iter()
+                .map(|id| SeatView { id: *id, name: self.name(id).to_string(), bot: self.bots.get(id).copied() })
+                .collect(),
+            variant: game.variant(),
+            status: game.status,
+            turn: game.turn,
+            current_player: game.current_player_index,
+            deck: game.board().deck.len(),
+            hands: game.players().iter().map(|player| player.hand.len()).collect(),
+            piles,
+            turn_clock: self.turn_clock,
+            advice: self.advice,
+        }
+    }
+}
+
+/// The publicly visible state of a room, without the cards in the hands of the players.
+#[derive(Debug, Serialize)]
+pub struct RoomView {
+    pub id: Uuid,
+    pub owner: Option<Uuid>,
+    /// The players in the order they are seated.
+    pub players: Vec<SeatView>,
+    pub variant: Variant,
+    pub status: GameStatus,
+    pub turn: usize,
+    /// The seat of the player who has the turn.
+    pub current_player: usize,
+    /// The number of cards left in the deck.
+    pub deck: usize,
+    /// The number of cards in the hand of each player.
+    pub hands: Vec<usize>,
+    pub piles: Vec<PileView>,
+    pub turn_clock: Option<TurnClock>,
+    /// Whether the players may ask for advice on their turns.
+    pub advice: bool,
+}
+
+/// A player seated in a room.
+#[derive(Debug, Serialize)]
+pub struct SeatView {
+    pub id: Uuid,
+    pub name: String,
+    /// The strategy of the player, if they are a computer player.
+    pub bot: Option<BuiltinStrategy>,
+}
+
+/// The publicly visible state of a discard pile.
+#[derive(Debug, Serialize)]
+pub struct PileView {
+    pub top: u8,
+    pub direction: Direction,
+}
+
+impl Default for Room {
+    fn default() -> Self {
+        Self {
+            players: LinkedHashSet::new(),
+            names: HashMap::new(),
+            bots: HashMap::new(),
+            game: Game::new(),
+            started_at: None,
+            active_at: SystemTime::now(),
+            turn_clock: None,
+            turn_started_at: None,
+            advice: false,
+        }
+    }
 }
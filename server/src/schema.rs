@@ -0,0 +1,40 @@
+//! JSON Schemas of the protocol, used to generate the types of the clients.
+use schemars::{schema_for, Schema};
+use the_game_core::analysis::Analysis;
+use crate::history::{GameRecord, GameSummary, PlayerStats};
+use crate::messages::{AdviceResponse, HelloResponse, MessageFromClient, MessageToClient, ReconnectResponse, RegisterResponse};
+use crate::protocol::{Notification, Request, Response};
+
+/// Returns the schema of every request, response and event type, keyed by name.
+pub fn schemas() -> Vec<(&'static str, Schema)> {
+    vec![
+        ("request", schema_for!(Request)),
+        ("message_from_client", schema_for!(MessageFromClient)),
+        ("response", schema_for!(Response)),
+        ("hello_response", schema_for!(HelloResponse)),
+        ("reconnect_response", schema_for!(ReconnectResponse)),
+        ("register_response", schema_for!(RegisterResponse)),
+        ("advice_response", schema_for!(AdviceResponse)),
+        ("player_stats", schema_for!(PlayerStats)),
+        ("game_summary", schema_for!(GameSummary)),
+        ("game_record", schema_for!(GameRecord)),
+        ("analysis", schema_for!(Analysis)),
+        ("notification", schema_for!(Notification<MessageToClient>)),
+        ("message_to_client", schema_for!(MessageToClient)),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::messages::MessageFromClient;
+    use crate::schema::schemas;
+
+    #[test]
+    fn test_every_method_is_described() {
+        let (_, schema) = schemas().into_iter().find(|(name, _)| *name == "message_from_client").unwrap();
+        let schema = serde_json::to_string(&schema).unwrap();
+        for method in MessageFromClient::METHODS {
+            assert!(schema.contains(&format!("\"{}\"", method)), "{} is not described", method);
+        }
+    }
+}
@@ -1,60 +1,310 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::sync::Arc;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
 use actix::prelude::*;
 use serde::Serialize;
+use tracing::{error, info};
 use uuid::Uuid;
+use the_game_core::game::Outcome;
+use crate::codec::{Codec, Frame};
+use crate::config::ServerConfig;
+use crate::error::GameError;
+use crate::history::{GameRecord, GameSummary, History};
+use crate::identity;
+use crate::messages::{ClosedReason, MessageToClient};
+use crate::metrics::{Metrics, MAILBOX_PROBE_INTERVAL};
+use crate::protocol::Notification;
 use crate::room::Room;
+use crate::storage::{RoomSnapshot, Storage};
 
 #[derive(Message)]
 #[rtype(result = "()")]
-pub struct Message(pub String);
+pub struct Message(pub Frame);
 
+/// A connected session.
 #[derive(Debug)]
+pub struct Client {
+    /// The send handle of the session.
+    pub addr: Recipient<Message>,
+    /// The encoding the session expects.
+    pub codec: Codec,
+}
+
+#[derive(Debug, Default)]
 pub struct GameServer {
     /// Map of all sessions and their send handles.
-    pub sessions: HashMap<Uuid, Recipient<Message>>,
+    pub sessions: HashMap<Uuid, Client>,
     /// Map of all available rooms.
     pub rooms: HashMap<Uuid, Room>,
+    /// Settings of the server.
+    pub config: Arc<ServerConfig>,
+    /// Counters exposed to monitoring.
+    pub metrics: Metrics,
+    /// The reconnect token of each session.
+    pub tokens: HashMap<Uuid, String>,
+    /// The display name of each registered session.
+    pub names: HashMap<Uuid, String>,
+    /// The finished games.
+    pub history: History,
+    /// Where rooms are saved, if anywhere.
+    storage: Option<Box<dyn Storage>>,
+    /// The rooms changed since they were last saved.
+    dirty: HashSet<Uuid>,
+    /// Set once the server has started shutting down, after which no room can be created.
+    pub shutting_down: bool,
 }
 
 impl GameServer {
-    /// Removes the given session from all rooms.
-    pub fn leave(&mut self, session_id: &Uuid) {
-        for (_, room) in &mut self.rooms {
-            room.leave(session_id);
+    /// Returns new `GameServer` struct with the given settings.
+    pub fn new(config: Arc<ServerConfig>) -> Self {
+        Self { config, ..Self::default() }
+    }
+
+    /// Restores the rooms saved in the given storage, and saves them there from now on.
+    pub fn restore(&mut self, mut storage: Box<dyn Storage>) -> io::Result<()> {
+        for (room_id, snapshot) in storage.load()? {
+            self.tokens.extend(snapshot.tokens);
+            self.rooms.insert(room_id, snapshot.room);
+        }
+
+        info!(rooms = self.rooms.len(), "Rooms restored");
+        self.storage = Some(storage);
+        Ok(())
+    }
+
+    /// Marks the given room as changed, saving it right away
+    /// unless rooms are saved periodically.
+    pub fn touch(&mut self, room_id: Uuid) {
+        if let Some(room) = self.rooms.get_mut(&room_id) {
+            room.active_at = SystemTime::now();
+        }
+
+        if self.storage.is_some() {
+            self.dirty.insert(room_id);
+            if self.config.snapshot_interval.is_zero() {
+                self.flush();
+            }
+        }
+    }
+
+    /// Saves every room, changed or not.
+    pub fn snapshot(&mut self) {
+        self.dirty.extend(self.rooms.keys());
+        self.flush();
+    }
+
+    /// Saves the rooms changed since they were last saved.
+    pub fn flush(&mut self) {
+        let Some(storage) = self.storage.as_mut() else {
+            return;
+        };
+
+        for room_id in self.dirty.drain() {
+            let result = match self.rooms.get(&room_id) {
+                Some(room) => {
+                    let tokens = room.players.iter()
+                        .filter_map(|id| Some((*id, self.tokens.get(id)?.clone())))
+                        .collect();
+                    storage.save(&room_id, &RoomSnapshot { room: room.clone(), tokens })
+                }
+                None => storage.delete(&room_id),
+            };
+
+            if let Err(err) = result {
+                error!(room = %room_id, %err, "Failed to save room");
+            }
+        }
+    }
+
+    /// Closes the rooms nothing happened in for too long, and the rooms left without human players
+    /// for a whole sweep interval, so that new rooms have time to be joined.
+    pub fn sweep(&mut self) {
+        let config = &self.config;
+        let expired = self.rooms.iter()
+            .filter_map(|(room_id, room)| {
+                if room.is_abandoned() && room.is_idle(config.sweep_interval) {
+                    Some((*room_id, ClosedReason::Empty))
+                } else if room.is_idle(config.room_idle_timeout) {
+                    Some((*room_id, ClosedReason::Idle))
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+
+        for (room_id, reason) in expired {
+            self.close_room(room_id, reason);
+        }
+    }
+
+    /// Removes the given room, telling its members why
+    /// and recording its game as abandoned if it was still running.
+    pub fn close_room(&mut self, room_id: Uuid, reason: ClosedReason) {
+        let Some(room) = self.rooms.get(&room_id) else {
+            return;
+        };
+
+        info!(room = %room_id, ?reason, players = room.len(), "Closing room");
+        if room.game.is_playing() {
+            self.record_game(&room_id, Outcome::Loss, true);
         }
+
+        self.send_message(&room_id, &MessageToClient::RoomClosed { room_id, reason });
+        let label = match reason {
+            ClosedReason::Empty => "empty",
+            ClosedReason::Idle => "idle",
+        };
+        *self.metrics.rooms_closed.entry(label).or_default() += 1;
+
+        if let Some(room) = self.rooms.remove(&room_id) {
+            // Disconnected players have no seat to take back anymore.
+            for player in &room.players {
+                if !self.sessions.contains_key(player) && !self.rooms.values().any(|room| room.contains(player)) {
+                    self.tokens.remove(player);
+                }
+            }
+        }
+
+        self.touch(room_id);
     }
 
-    /// Creates a new room for a given player.
-    pub fn create_room(&mut self, room_id: Uuid, session_id: Uuid) {
-        let mut room = Room::new();
-        room.join(session_id);
+    /// Frees the seats of a disconnected session whose reconnect grace period is over,
+    /// unless it came back in the meantime.
+    /// Seats in running games are kept, along with the token, as the game can't go on without them.
+    pub fn expire_seats(&mut self, session_id: &Uuid) {
+        if self.sessions.contains_key(session_id) || !self.tokens.contains_key(session_id) {
+            return;
+        }
+
+        let mut rooms = Vec::new();
+        for (room_id, room) in self.rooms.iter_mut() {
+            if room.contains(session_id) && room.leave(session_id).is_ok() {
+                rooms.push(*room_id);
+            }
+        }
+
+        info!(session = %session_id, rooms = rooms.len(), "Reconnect grace period over");
+        for room_id in rooms {
+            self.touch(room_id);
+        }
+
+        if !self.rooms.values().any(|room| room.contains(session_id)) {
+            self.tokens.remove(session_id);
+        }
+    }
+
+    /// Creates a new room, owned by the given player if any.
+    /// A room without an owner is owned by the first player to join.
+    pub fn create_room(&mut self, room_id: Uuid, owner: Option<Uuid>) -> Result<(), GameError> {
+        if self.shutting_down {
+            return Err(GameError::ShuttingDown);
+        }
+        if self.rooms.len() >= self.config.max_rooms {
+            return Err(GameError::TooManyRooms);
+        }
+
+        let mut room = Room::with_variant(self.config.variant);
+        if let Some(owner) = owner {
+            room.join(owner, self.name_of(&owner))?;
+        }
+
         self.rooms.insert(room_id, room);
+        self.touch(room_id);
+        Ok(())
+    }
+
+    /// Returns the display name of the given session.
+    pub fn name_of(&self, session_id: &Uuid) -> String {
+        self.names.get(session_id).cloned().unwrap_or_else(|| identity::guest_name(session_id))
+    }
+
+    /// Returns the room with the given id.
+    pub fn room(&self, room_id: &Uuid) -> Result<&Room, GameError> {
+        self.rooms.get(room_id).ok_or(GameError::RoomNotFound)
+    }
+
+    /// Returns the room with the given id.
+    pub fn room_mut(&mut self, room_id: &Uuid) -> Result<&mut Room, GameError> {
+        self.rooms.get_mut(room_id).ok_or(GameError::RoomNotFound)
+    }
+
+    /// Announces the end of the game in the given room, if it is over,
+    /// and records it in the history.
+    pub fn announce_game_over(&mut self, room_id: &Uuid) {
+        let Some(room) = self.rooms.get(room_id) else {
+            return;
+        };
+
+        let Some(outcome) = room.game.outcome() else {
+            return;
+        };
+
+        let score = room.game.score();
+        info!(room = %room_id, ?outcome, score, "Game over");
+        self.metrics.game_over(outcome, score);
+        self.send_message(room_id, &MessageToClient::GameOver { outcome, score });
+        self.record_game(room_id, outcome, false);
+    }
+
+    /// Records the game of the given room in the history.
+    fn record_game(&mut self, room_id: &Uuid, outcome: Outcome, abandoned: bool) {
+        let Some(room) = self.rooms.get(room_id) else {
+            return;
+        };
+
+        let started_at = room.started_at.unwrap_or_else(SystemTime::now);
+        let summary = GameSummary {
+            id: Uuid::new_v4(),
+            room_id: *room_id,
+            variant: room.game.variant(),
+            seed: room.game.seed(),
+            players: room.players.iter().copied().collect(),
+            names: room.players.iter().map(|id| room.name(id).to_string()).collect(),
+            outcome,
+            score: room.game.score(),
+            started_at: started_at.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs(),
+            duration: started_at.elapsed().unwrap_or_default().as_secs(),
+            abandoned,
+        };
+
+        let record = GameRecord { summary, log: room.game.log().to_vec() };
+        if let Err(err) = self.history.record(record) {
+            error!(room = %room_id, %err, "Failed to record game");
+        }
     }
 
     /// Sends a message to all players in the room.
     pub fn send_message<T>(&self, room: &Uuid, msg: &T) where T: Serialize {
         if let Some(room) = self.rooms.get(room) {
+            let notification = Notification::new(msg);
+            // Each message is encoded once per codec in use.
+            let mut frames = HashMap::new();
             for session_id in &room.players {
-                if let Some(session) = self.sessions.get(session_id) {
-                    let message = Message(serde_json::to_string(&msg).unwrap());
-                    session.do_send(message);
+                if let Some(client) = self.sessions.get(session_id) {
+                    let frame = frames.entry(client.codec).or_insert_with(|| client.codec.encode(&notification));
+                    client.addr.do_send(Message(frame.clone()));
                 }
             }
         }
     }
 
-    /// Sends a message to the given user.
-    pub fn send_message_to<T>(&self, receiver_id: &Uuid, msg: &T) where T: Serialize {
-        if let Some(session) = self.sessions.get(receiver_id) {
-            let message = Message(serde_json::to_string(&msg).unwrap());
-            session.do_send(message);
+    /// Sends a message to every session.
+    pub fn broadcast<T>(&self, msg: &T) where T: Serialize {
+        let notification = Notification::new(msg);
+        let mut frames = HashMap::new();
+        for client in self.sessions.values() {
+            let frame = frames.entry(client.codec).or_insert_with(|| client.codec.encode(&notification));
+            client.addr.do_send(Message(frame.clone()));
         }
     }
-}
 
-impl Default for GameServer {
-    fn default() -> Self {
-        Self { rooms: HashMap::new(), sessions: HashMap::new() }
+    /// Sends a message to the given user.
+    pub fn send_message_to<T>(&self, receiver_id: &Uuid, msg: &T) where T: Serialize {
+        if let Some(client) = self.sessions.get(receiver_id) {
+            let message = Message(client.codec.encode(&Notification::new(msg)));
+            client.addr.do_send(message);
+        }
     }
 }
 
@@ -63,4 +313,157 @@ impl Actor for GameServer {
     /// We are going to use simple Context, we just need ability to communicate
     /// with other actors.
     type Context = Context<Self>;
+
+    /// Starts measuring the latency of the mailbox, closing empty and idle rooms,
+    /// saving the rooms periodically if configured to, and running the turn clocks.
+    fn started(&mut self, ctx: &mut Self::Context) {
+        ctx.run_interval(MAILBOX_PROBE_INTERVAL, |_, ctx| {
+            ctx.address().do_send(Probe(Instant::now()));
+        });
+
+        if !self.config.snapshot_interval.is_zero() {
+            ctx.run_interval(self.config.snapshot_interval, |act, _| act.flush());
+        }
+
+        ctx.run_interval(self.config.sweep_interval, |act, _| act.sweep());
+
+        // The clocks of the restored games start over.
+        for room_id in self.rooms.keys().copied().collect::<Vec<_>>() {
+            self.start_turn(room_id, ctx);
+        }
+
+        // The players of the restored rooms have the grace period to reconnect.
+        for session_id in self.tokens.keys().copied().collect::<Vec<_>>() {
+            ctx.run_later(self.config.reconnect_grace, move |act, _| act.expire_seats(&session_id));
+        }
+    }
+
+    /// Saves the rooms that have not been saved yet.
+    fn stopped(&mut self, _: &mut Self::Context) {
+        self.flush();
+    }
+}
+
+/// A message sent by the game server to itself to measure
+/// how long messages wait in its mailbox.
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct Probe(pub Instant);
+
+impl Handler<Probe> for GameServer {
+    type Result = ();
+
+    /// Handles `Probe` message.
+    fn handle(&mut self, msg: Probe, _: &mut Context<Self>) {
+        self.metrics.mailbox_latency.observe(msg.0.elapsed().as_secs_f64());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+    use actix::{Actor, ActorContext, Context, Handler};
+    use uuid::Uuid;
+    use crate::codec::{Codec, Frame};
+    use crate::error::GameError;
+    use crate::handlers::prelude::{Connect, Disconnect, LeaveRoom};
+    use crate::server::{GameServer, Message};
+
+    /// A connection, which stops once told to close.
+    struct Peer;
+
+    impl Actor for Peer {
+        type Context = Context<Self>;
+    }
+
+    impl Handler<Message> for Peer {
+        type Result = ();
+
+        fn handle(&mut self, msg: Message, ctx: &mut Context<Self>) {
+            if let Frame::Close(_) = msg.0 {
+                ctx.stop();
+            }
+        }
+    }
+
+    #[test]
+    fn test_sweep() {
+        let mut server = GameServer::default();
+        let (empty, idle, active, player) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        server.create_room(empty, None).unwrap();
+        server.create_room(idle, Some(player)).unwrap();
+        server.create_room(active, Some(player)).unwrap();
+        server.rooms.get_mut(&idle).unwrap().start_game().unwrap();
+
+        let long_ago = SystemTime::now() - Duration::from_secs(24 * 60 * 60);
+        server.rooms.get_mut(&empty).unwrap().active_at = long_ago;
+        server.rooms.get_mut(&idle).unwrap().active_at = long_ago;
+        server.sweep();
+
+        assert_eq!(server.rooms.keys().collect::<Vec<_>>(), vec![&active]);
+        let games = server.history.games(Some(&player)).collect::<Vec<_>>();
+        assert_eq!(games.len(), 1);
+        assert!(games[0].abandoned);
+    }
+
+    #[actix_web::test]
+    async fn test_leave_running_game() {
+        let mut server = GameServer::default();
+        let mut ctx = Context::new();
+        let (room_id, alice, bob) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        server.create_room(room_id, Some(alice)).unwrap();
+        server.rooms.get_mut(&room_id).unwrap().join(bob, "Bob".to_string()).unwrap();
+        server.rooms.get_mut(&room_id).unwrap().start_game().unwrap();
+
+        let result = server.handle(LeaveRoom { room_id, session: bob }, &mut ctx);
+        assert_eq!(result, Err(GameError::Rejected("Can't leave a running game")));
+        assert_eq!(server.rooms[&room_id].player_index(&bob), Some(1));
+
+        server.rooms.get_mut(&room_id).unwrap().forfeit().unwrap();
+        server.handle(LeaveRoom { room_id, session: bob }, &mut ctx).unwrap();
+        assert_eq!(server.rooms[&room_id].player_index(&bob), None);
+    }
+
+    #[actix_web::test]
+    async fn test_reconnect_grace() {
+        let mut server = GameServer::default();
+        let mut ctx = Context::new();
+        let (waiting, running, alice, bob) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        server.create_room(waiting, Some(alice)).unwrap();
+        server.rooms.get_mut(&waiting).unwrap().join(bob, "Bob".to_string()).unwrap();
+        server.create_room(running, Some(alice)).unwrap();
+        server.rooms.get_mut(&running).unwrap().start_game().unwrap();
+        server.tokens.extend([(alice, "alice".to_string()), (bob, "bob".to_string())]);
+
+        let peer = Peer.start().recipient();
+        server.handle(Disconnect { id: alice, addr: peer.clone() }, &mut ctx);
+        server.handle(Disconnect { id: bob, addr: peer }, &mut ctx);
+        assert_eq!(server.tokens.len(), 2);
+
+        server.expire_seats(&alice);
+        server.expire_seats(&bob);
+        assert!(!server.rooms[&waiting].contains(&alice) && !server.rooms[&waiting].contains(&bob));
+        assert!(server.rooms[&running].contains(&alice));
+        assert_eq!(server.tokens.keys().collect::<Vec<_>>(), vec![&alice]);
+    }
+
+    #[actix_web::test]
+    async fn test_identity_takeover() {
+        let mut server = GameServer::default();
+        let mut ctx = Context::new();
+        let (player, old, new) = (Uuid::new_v4(), Peer.start(), Peer.start());
+        let connect = |addr| Connect { id: player, addr, codec: Codec::Json, token: "token".to_string(), name: Some("Alice".to_string()) };
+        server.handle(connect(old.clone().recipient()), &mut ctx);
+        server.handle(connect(new.clone().recipient()), &mut ctx);
+
+        // The connection that was taken over is closed, and its disconnection leaves the new one alone.
+        server.handle(Disconnect { id: player, addr: old.clone().recipient() }, &mut ctx);
+        assert!(server.sessions.contains_key(&player));
+        assert_eq!(server.name_of(&player), "Alice");
+        actix_web::rt::time::sleep(Duration::from_millis(10)).await;
+        assert!(!old.connected() && new.connected());
+
+        server.handle(Disconnect { id: player, addr: new.recipient() }, &mut ctx);
+        assert!(!server.sessions.contains_key(&player));
+    }
 }
@@ -1,43 +1,78 @@
-use std::time::{Duration, Instant};
+use std::sync::Arc;
+use std::time::Instant;
 use actix::prelude::*;
 use actix_web_actors::ws;
+use futures_util::future::{self, join_all, FutureExt, LocalBoxFuture};
 use serde::Serialize;
+use serde_json::{json, Value};
+use tracing::{error, info, warn};
 use uuid::Uuid;
+use crate::codec::{Codec, Frame};
+use crate::config::ServerConfig;
+use crate::error::GameError;
 use crate::handlers::prelude::*;
+use crate::identity::{self, Identity};
 use crate::protocol;
-use crate::messages::MessageFromClient;
+use crate::rate_limit::RateLimiter;
+use crate::messages::{AddBotMessage, AdviceMessage, GameMessage, HelloMessage, HelloResponse, MessageFromClient, PlayerMessage, ReconnectMessage, RegisterMessage, RoomMessage, TurnClockMessage};
 use crate::server;
 
-/// How often heartbeat pings are sent.
-const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
-
-/// How long before lack of client response causes a timeout.
-const CLIENT_TIMEOUT: Duration = Duration::from_secs(30);
-
 /// An actor representing a websocket connection.
 #[derive(Debug)]
 pub struct Session {
-    /// Client must send ping at least once per 30 seconds (CLIENT_TIMEOUT),
+    /// Client must send ping at least once per `client_timeout`,
     /// otherwise we drop connection.
     hb: Instant,
     /// Unique session id.
     id: Uuid,
     /// Game server.
     addr: Addr<server::GameServer>,
+    /// The protocol version agreed on during the handshake.
+    protocol_version: Option<u32>,
+    /// Set when the session has been rejected and must be closed.
+    close_reason: Option<ws::CloseReason>,
+    /// The encoding of the frames sent to the client.
+    codec: Codec,
+    /// The encoding requested during the handshake, applied once it has been answered.
+    next_codec: Option<Codec>,
+    /// The identity proven during the handshake, taken on once it has been answered.
+    next_identity: Option<Identity>,
+    /// Settings of the server.
+    config: Arc<ServerConfig>,
+    /// The rate limits of the methods called by the client.
+    limiter: RateLimiter,
+    /// How many times the client went over its limits.
+    violations: u32,
+    /// The secret letting a later session take back the seats of this one.
+    token: String,
 }
 
 impl Session {
     /// Returns new `Session` struct initialized with default values.
-    pub fn new(id: Uuid, addr: Addr<server::GameServer>) -> Self {
-        Self { hb: Instant::now(), id, addr }
+    pub fn new(id: Uuid, addr: Addr<server::GameServer>, codec: Codec, config: Arc<ServerConfig>) -> Self {
+        Self {
+            hb: Instant::now(),
+            id,
+            addr,
+            protocol_version: None,
+            close_reason: None,
+            codec,
+            next_codec: None,
+            next_identity: None,
+            config,
+            limiter: RateLimiter::default(),
+            violations: 0,
+            token: Uuid::new_v4().simple().to_string(),
+        }
     }
 
-    /// Sends ping to client every `HEARTBEAT_INTERVAL`.
+    /// Sends ping to client every `heartbeat_interval`.
     /// Also this method checks heartbeats from client and disconnects unresponsive clients.
     fn hb(&self, ctx: &mut ws::WebsocketContext<Self>) {
-        ctx.run_interval(HEARTBEAT_INTERVAL, |act, ctx| {
-            if Instant::now().duration_since(act.hb) > CLIENT_TIMEOUT {
-                act.addr.do_send(Disconnect(act.id));
+        ctx.run_interval(self.config.heartbeat_interval, |act, ctx| {
+            if Instant::now().duration_since(act.hb) > act.config.client_timeout {
+                info!(session = %act.id, "Client timed out");
+                act.addr.do_send(Disconnect { id: act.id, addr: ctx.address().recipient() });
                 ctx.stop();
                 return;
             }
@@ -46,32 +81,279 @@ impl Session {
         });
     }
 
-    /// Sends the given message to the websockets actor and waits for response.
-    fn send_message<M, T>(&mut self, request_id: protocol::Id, msg: M, ctx: &mut ws::WebsocketContext<Self>)
-        where M: actix::Message<Result=Result<T, &'static str>> + Send + 'static,
+    /// Handles a frame, answering every call in it that expects a response.
+    fn handle_frame(&mut self, codec: Codec, data: &[u8], ctx: &mut ws::WebsocketContext<Self>) {
+        match protocol::decode(codec, data) {
+            Ok(protocol::Payload::Single(call)) => {
+                self.call(call)
+                    .into_actor(self)
+                    .then(|response, act, ctx| {
+                        if let Some(response) = response {
+                            act.write(&response, ctx)
+                        }
+
+                        act.after_reply(ctx);
+                        fut::ready(())
+                    })
+                    .wait(ctx)
+            }
+            Ok(protocol::Payload::Batch(calls)) => {
+                let calls = calls.into_iter().map(|call| self.call(call)).collect::<Vec<_>>();
+                join_all(calls)
+                    .into_actor(self)
+                    .then(|responses, act, ctx| {
+                        let responses = responses.into_iter().flatten().collect::<Vec<_>>();
+                        // A batch of notifications is never answered.
+                        if !responses.is_empty() {
+                            act.write(&responses, ctx)
+                        }
+
+                        act.after_reply(ctx);
+                        fut::ready(())
+                    })
+                    .wait(ctx)
+            }
+            Err(response) => {
+                warn!(session = %self.id, ?codec, "Failed to decode frame");
+                self.write(&response, ctx)
+            }
+        }
+    }
+
+    /// Dispatches a single call to the game server.
+    /// Resolves to the response, or `None` if the call is a notification.
+    fn call(&mut self, call: Value) -> LocalBoxFuture<'static, Option<protocol::Response>> {
+        let request = match protocol::Request::from_value(call) {
+            Ok(request) => request,
+            Err(response) => {
+                warn!(session = %self.id, "Invalid request");
+                return Box::pin(future::ready(Some(response)));
+            }
+        };
+
+        let id = request.id.clone();
+        let method = request.method.clone();
+        let message = match request.into_message() {
+            Ok(message) => message,
+            Err(err) => {
+                warn!(session = %self.id, method, error = err.message, "Request rejected");
+                let response = id.map(|id| protocol::Response::failure(Some(id), err));
+                return Box::pin(future::ready(response));
+            }
+        };
+
+        if let Err(retry_after) = self.limiter.check(&method, &self.config, Instant::now()) {
+            self.violation(&method);
+            let err = protocol::Error::new(protocol::ErrorCode::RateLimited)
+                .with_data(json!({ "retry_after": retry_after.as_secs_f64() }));
+            return Box::pin(future::ready(id.map(|id| protocol::Response::failure(Some(id), err))));
+        }
+
+        if self.protocol_version.is_none() && !matches!(message, MessageFromClient::Hello(_)) {
+            warn!(session = %self.id, method, "Request before handshake");
+            let err = protocol::Error::new(protocol::ErrorCode::HandshakeRequired);
+            return Box::pin(future::ready(id.map(|id| protocol::Response::failure(Some(id), err))));
+        }
+
+        match message {
+            MessageFromClient::Hello(hello) => {
+                Box::pin(future::ready(self.hello(id, hello)))
+            }
+            MessageFromClient::CreateRoom => {
+                self.send_message(id, CreateRoom(Some(self.id)))
+            }
+            MessageFromClient::JoinRoom(RoomMessage { room_id }) => {
+                self.send_message(id, JoinRoom { room_id, session: self.id })
+            }
+            MessageFromClient::LeaveRoom(RoomMessage { room_id }) => {
+                self.send_message(id, LeaveRoom { room_id, session: self.id })
+            }
+            MessageFromClient::StartGame(RoomMessage { room_id }) => {
+                self.send_message(id, StartGame { room_id, session: self.id })
+            }
+            MessageFromClient::EndTurn(RoomMessage { room_id }) => {
+                self.send_message(id, EndTurn { room_id, session: self.id })
+            }
+            MessageFromClient::Register(RegisterMessage { name }) => {
+                self.send_message(id, Register { name, session: self.id })
+            }
+            MessageFromClient::SetTurnClock(TurnClockMessage { room_id, clock }) => {
+                self.send_message(id, SetTurnClock { room_id, session: self.id, clock })
+            }
+            MessageFromClient::AddBot(AddBotMessage { room_id, strategy }) => {
+                self.send_message(id, AddBot { room_id, session: self.id, strategy })
+            }
+            MessageFromClient::SetAdvice(AdviceMessage { room_id, enabled }) => {
+                self.send_message(id, SetAdvice { room_id, session: self.id, enabled })
+            }
+            MessageFromClient::RequestAdvice(RoomMessage { room_id }) => {
+                self.send_message(id, RequestAdvice { room_id, session: self.id })
+            }
+            MessageFromClient::Reconnect(ReconnectMessage { token }) => {
+                self.send_message(id, Reconnect { token, session: self.id })
+            }
+            MessageFromClient::ListGames(PlayerMessage { player }) => {
+                self.send_query(id, ListGames(Some(player.unwrap_or(self.id))))
+            }
+            MessageFromClient::GetGame(GameMessage { game_id }) => {
+                self.send_message(id, GetGame(game_id))
+            }
+            MessageFromClient::AnalyzeGame(GameMessage { game_id }) => {
+                self.send_message(id, AnalyzeGame(game_id))
+            }
+            MessageFromClient::PlayerStats(PlayerMessage { player }) => {
+                self.send_query(id, GetPlayerStats(player.unwrap_or(self.id)))
+            }
+            MessageFromClient::DiscardCard(message) => {
+                let msg = DiscardCard {
+                    card: message.card,
+                    position: message.position,
+                    room_id: message.room_id,
+                    session: self.id,
+                };
+
+                self.send_message(id, msg)
+            }
+        }
+    }
+
+    /// Agrees on the protocol version to use for the rest of the session.
+    /// A client speaking an unsupported version is disconnected.
+    fn hello(&mut self, id: Option<protocol::Id>, msg: HelloMessage) -> Option<protocol::Response> {
+        if !protocol::SUPPORTED_VERSIONS.contains(&msg.protocol_version) {
+            warn!(session = %self.id, version = msg.protocol_version, client = msg.client, "Unsupported protocol version");
+            self.close_reason = Some(ws::CloseReason {
+                code: ws::CloseCode::Protocol,
+                description: Some(protocol::ErrorCode::UnsupportedVersion.message().to_string()),
+            });
+
+            let err = protocol::Error::new(protocol::ErrorCode::UnsupportedVersion)
+                .with_data(json!({ "supported_versions": protocol::SUPPORTED_VERSIONS }));
+            return id.map(|id| protocol::Response::failure(Some(id), err));
+        }
+
+        let identity = match msg.player_token.as_deref().map(|token| Identity::verify(&self.config.secret, token)) {
+            Some(None) => {
+                warn!(session = %self.id, "Invalid player token");
+                let err = protocol::Error::new(protocol::ErrorCode::InvalidToken);
+                return id.map(|id| protocol::Response::failure(Some(id), err));
+            }
+            Some(identity) => identity,
+            None => None,
+        };
+
+        info!(session = %self.id, version = msg.protocol_version, client = msg.client, codec = ?msg.codec, "Handshake completed");
+        self.protocol_version = Some(msg.protocol_version);
+        self.next_codec = msg.codec;
+        let response = HelloResponse {
+            protocol_version: msg.protocol_version,
+            supported_versions: protocol::SUPPORTED_VERSIONS.to_vec(),
+            capabilities: protocol::CAPABILITIES.iter().map(|x| x.to_string()).collect(),
+            codec: msg.codec.unwrap_or(self.codec),
+            reconnect_token: self.token.clone(),
+            player_id: identity.as_ref().map_or(self.id, |identity| identity.id),
+            name: identity.as_ref().map_or_else(|| identity::guest_name(&self.id), |identity| identity.name.clone()),
+        };
+
+        self.next_identity = identity;
+
+        id.map(|id| protocol::Response::success(id, response))
+    }
+
+    /// Counts a call over the rate limit of its method.
+    /// The session is closed once it has gone over its limits too many times.
+    fn violation(&mut self, method: &str) {
+        self.violations += 1;
+        warn!(session = %self.id, method, violations = self.violations, "Rate limit exceeded");
+        if self.violations >= self.config.max_violations && self.close_reason.is_none() {
+            warn!(session = %self.id, "Disconnecting session over its rate limits");
+            self.close_reason = Some(ws::CloseReason {
+                code: ws::CloseCode::Policy,
+                description: Some(protocol::ErrorCode::RateLimited.message().to_string()),
+            });
+        }
+    }
+
+    /// Applies the outcome of the handshake once its response has been sent,
+    /// either switching the codec or closing the connection if the session has been rejected.
+    fn after_reply(&mut self, ctx: &mut ws::WebsocketContext<Self>) {
+        let mut name = None;
+        if let Some(identity) = self.next_identity.take() {
+            info!(session = %self.id, player = %identity.id, name = identity.name, "Identity restored");
+            self.addr.do_send(Disconnect { id: self.id, addr: ctx.address().recipient() });
+            self.id = identity.id;
+            name = Some(identity.name);
+        }
+
+        let codec = self.next_codec.take();
+        if let Some(codec) = codec {
+            self.codec = codec;
+        }
+
+        if name.is_some() || codec.is_some() {
+            let recipient = ctx.address().recipient();
+            self.addr.do_send(Connect { id: self.id, addr: recipient, codec: self.codec, token: self.token.clone(), name });
+        }
+
+        if let Some(reason) = self.close_reason.take() {
+            ctx.close(Some(reason));
+            ctx.stop();
+        }
+    }
+
+    /// Sends the given value to the client, encoded with the codec of this session.
+    fn write<T>(&self, value: &T, ctx: &mut ws::WebsocketContext<Self>) where T: Serialize {
+        Self::send_frame(self.codec.encode(value), ctx)
+    }
+
+    /// Sends an encoded frame to the client.
+    fn send_frame(frame: Frame, ctx: &mut ws::WebsocketContext<Self>) {
+        match frame {
+            Frame::Text(text) => ctx.text(text),
+            Frame::Binary(data) => ctx.binary(data),
+            Frame::Close(reason) => {
+                ctx.close(reason);
+                ctx.stop();
+            }
+        }
+    }
+
+    /// Sends the given message to the game server and waits for response.
+    fn send_message<M, T>(&self, id: Option<protocol::Id>, msg: M) -> LocalBoxFuture<'static, Option<protocol::Response>>
+        where M: actix::Message<Result=Result<T, GameError>> + Send + 'static,
               M::Result: Send,
               server::GameServer: Handler<M>,
               T: Serialize
     {
-        self.addr
-            .send(msg)
-            .into_actor(self)
-            .then(|res, _, ctx| {
-                match res {
-                    Ok(Ok(val)) => {
-                        let msg = protocol::Response::success(request_id, &val);
-                        ctx.text(serde_json::to_string(&msg).unwrap())
-                    }
-                    Ok(Err(err)) => {
-                        let msg = protocol::Response::failure(request_id, err);
-                        ctx.text(serde_json::to_string(&msg).unwrap())
-                    }
-                    _ => {}
-                }
-
-                fut::ready(())
-            })
-            .wait(ctx)
+        self.addr.send(msg).map(move |res| Self::respond(id, res)).boxed_local()
+    }
+
+    /// Sends the given message, which can't be refused, to the game server and waits for response.
+    fn send_query<M, T>(&self, id: Option<protocol::Id>, msg: M) -> LocalBoxFuture<'static, Option<protocol::Response>>
+        where M: actix::Message<Result=T> + Send + 'static,
+              M::Result: Send,
+              server::GameServer: Handler<M>,
+              T: Serialize
+    {
+        self.addr.send(msg).map(move |res| Self::respond(id, res.map(Ok))).boxed_local()
+    }
+
+    /// Turns the answer of the game server into the response to the request with the given id.
+    fn respond<T>(id: Option<protocol::Id>, res: Result<Result<T, GameError>, MailboxError>) -> Option<protocol::Response>
+        where T: Serialize
+    {
+        let id = id?;
+        let response = match res {
+            Ok(Ok(val)) => protocol::Response::success(id, val),
+            Ok(Err(err)) => protocol::Response::failure(Some(id), err.into()),
+            Err(err) => {
+                error!(%err, "Game server is unreachable");
+                let err = protocol::Error::new(protocol::ErrorCode::InternalError).with_data(err.to_string());
+                protocol::Response::failure(Some(id), err)
+            }
+        };
+
+        Some(response)
     }
 }
 
@@ -85,12 +367,12 @@ impl Actor for Session {
         self.hb(ctx);
         // Register self in game server.
         let recipient = ctx.address().recipient();
-        self.addr.do_send(Connect { id: self.id, addr: recipient })
+        self.addr.do_send(Connect { id: self.id, addr: recipient, codec: self.codec, token: self.token.clone(), name: None })
     }
 
     /// Called after an actor is in Actor::Stopping state.
-    fn stopping(&mut self, _: &mut Self::Context) -> Running {
-        self.addr.do_send(Disconnect(self.id));
+    fn stopping(&mut self, ctx: &mut Self::Context) -> Running {
+        self.addr.do_send(Disconnect { id: self.id, addr: ctx.address().recipient() });
         Running::Stop
     }
 }
@@ -100,7 +382,7 @@ impl Handler<server::Message> for Session {
 
     /// Handles messages from game server, we simply send it to peer websocket.
     fn handle(&mut self, msg: server::Message, ctx: &mut Self::Context) {
-        ctx.text(msg.0);
+        Self::send_frame(msg.0, ctx);
     }
 }
 
@@ -116,39 +398,20 @@ impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for Session {
             Ok(ws::Message::Pong(_)) => {
                 self.hb = Instant::now()
             }
-            Ok(ws::Message::Text(text)) => {
-                match serde_json::from_str::<protocol::Request>(&text) {
-                    Ok(protocol::Request { id, payload }) => {
-                        match payload {
-                            MessageFromClient::CreateRoom => {
-                                let msg = CreateRoom(self.id);
-                                self.send_message(id, msg, ctx)
-                            }
-                            MessageFromClient::StartGame(room_id) => {
-                                let msg = StartGame { room_id, session: self.id };
-                                self.send_message(id, msg, ctx)
-                            }
-                            MessageFromClient::EndTurn(room_id) => {
-                                let msg = EndTurn { room_id, session: self.id };
-                                self.send_message(id, msg, ctx)
-                            }
-                            MessageFromClient::DiscardCard(message) => {
-                                let msg = DiscardCard {
-                                    card: message.card,
-                                    position: message.position,
-                                    room_id: message.room_id,
-                                    session: self.id,
-                                };
-
-                                self.send_message(id, msg, ctx)
-                            }
-                            _ => println!("Unhandled event")
-                        }
-                    }
-                    _ => println!("Failed to parse json")
-                }
-            }
+            Ok(ws::Message::Text(text)) => self.handle_frame(Codec::Json, text.as_bytes(), ctx),
+            Ok(ws::Message::Binary(data)) => self.handle_frame(Codec::MessagePack, &data, ctx),
             Ok(ws::Message::Close(_)) => ctx.stop(),
+            Err(ws::ProtocolError::Overflow) => {
+                warn!(session = %self.id, max_frame_size = self.config.max_frame_size, "Frame too large");
+                let err = protocol::Error::new(protocol::ErrorCode::FrameTooLarge)
+                    .with_data(json!({ "max_frame_size": self.config.max_frame_size }));
+                self.write(&protocol::Response::failure(None, err), ctx);
+                ctx.close(Some(ws::CloseReason {
+                    code: ws::CloseCode::Size,
+                    description: Some(protocol::ErrorCode::FrameTooLarge.message().to_string()),
+                }));
+                ctx.stop();
+            }
             _ => ctx.stop(),
         }
     }
@@ -0,0 +1,46 @@
+//! The shutdown sequence of the server, started by `SIGINT` or `SIGTERM`.
+use std::time::Duration;
+use actix::Addr;
+use actix_web::dev::ServerHandle;
+use actix_web::rt::{signal, time};
+use futures_util::future::{self, Either};
+use tracing::{error, info, warn};
+use crate::handlers::prelude::*;
+use crate::server::GameServer;
+
+/// Waits for a signal to stop, then shuts the game server and the HTTP server down,
+/// stopping them abruptly if it takes longer than `timeout`.
+pub async fn on_signal(srv: Addr<GameServer>, http: ServerHandle, timeout: Duration) {
+    wait_for_signal().await;
+    let sequence = async {
+        if let Err(err) = srv.send(Shutdown).await {
+            error!(%err, "Game server is unreachable");
+        }
+
+        http.stop(true).await;
+    };
+
+    if time::timeout(timeout, sequence).await.is_err() {
+        warn!(?timeout, "Shutdown timed out, stopping now");
+        http.stop(false).await;
+    }
+}
+
+/// Resolves once the process is asked to stop.
+async fn wait_for_signal() {
+    let ctrl_c = Box::pin(signal::ctrl_c());
+    #[cfg(unix)]
+    let terminate = Box::pin(async {
+        match signal::unix::signal(signal::unix::SignalKind::terminate()) {
+            Ok(mut terminate) => terminate.recv().await,
+            Err(_) => future::pending().await,
+        }
+    });
+    #[cfg(not(unix))]
+    let terminate = Box::pin(future::pending::<Option<()>>());
+
+    match future::select(ctrl_c, terminate).await {
+        Either::Left(_) => info!("Received SIGINT"),
+        Either::Right(_) => info!("Received SIGTERM"),
+    }
+}
@@ -0,0 +1,120 @@
+//! Persistence of the rooms, so that games survive a restart of the server.
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+use uuid::Uuid;
+use crate::room::Room;
+
+/// Everything needed to restore a room.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RoomSnapshot {
+    pub room: Room,
+    /// The reconnect tokens of the players in the room.
+    pub tokens: HashMap<Uuid, String>,
+}
+
+/// A place where rooms are saved.
+pub trait Storage: Debug {
+    /// Saves the given room, replacing its previous snapshot.
+    fn save(&mut self, room_id: &Uuid, snapshot: &RoomSnapshot) -> io::Result<()>;
+
+    /// Deletes the snapshot of the given room, if any.
+    fn delete(&mut self, room_id: &Uuid) -> io::Result<()>;
+
+    /// Loads the snapshots of all rooms.
+    fn load(&mut self) -> io::Result<Vec<(Uuid, RoomSnapshot)>>;
+}
+
+/// Saves each room as a JSON file in a directory.
+#[derive(Debug)]
+pub struct FileStorage {
+    dir: PathBuf,
+}
+
+impl FileStorage {
+    /// Returns a storage writing to the given directory, creating it if needed.
+    pub fn new(dir: &Path) -> io::Result<Self> {
+        fs::create_dir_all(dir)?;
+        Ok(Self { dir: dir.to_path_buf() })
+    }
+
+    fn path(&self, room_id: &Uuid) -> PathBuf {
+        self.dir.join(format!("{}.json", room_id))
+    }
+}
+
+impl Storage for FileStorage {
+    fn save(&mut self, room_id: &Uuid, snapshot: &RoomSnapshot) -> io::Result<()> {
+        // The snapshot is written aside first, so that a crash never leaves a truncated file behind.
+        let path = self.path(room_id);
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_vec(snapshot)?)?;
+        fs::rename(tmp, path)
+    }
+
+    fn delete(&mut self, room_id: &Uuid) -> io::Result<()> {
+        match fs::remove_file(self.path(room_id)) {
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+            result => result,
+        }
+    }
+
+    fn load(&mut self) -> io::Result<Vec<(Uuid, RoomSnapshot)>> {
+        let mut snapshots = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+
+            let Some(room_id) = path.file_stem().and_then(|stem| stem.to_str()?.parse().ok()) else {
+                warn!(path = %path.display(), "Skipping file not named after a room");
+                continue;
+            };
+
+            match serde_json::from_slice(&fs::read(&path)?) {
+                Ok(snapshot) => snapshots.push((room_id, snapshot)),
+                Err(err) => warn!(path = %path.display(), %err, "Skipping unreadable room snapshot"),
+            }
+        }
+
+        Ok(snapshots)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use uuid::Uuid;
+    use crate::room::Room;
+    use crate::storage::{FileStorage, RoomSnapshot, Storage};
+
+    #[test]
+    fn test_file_storage() {
+        let dir = std::env::temp_dir().join(format!("thegame-{}", Uuid::new_v4()));
+        let mut storage = FileStorage::new(&dir).unwrap();
+        let (room_id, player) = (Uuid::new_v4(), Uuid::new_v4());
+        let mut room = Room::new();
+        room.join(player, "Alice".to_string()).unwrap();
+        room.start_game().unwrap();
+
+        let tokens = HashMap::from([(player, "token".to_string())]);
+        storage.save(&room_id, &RoomSnapshot { room: room.clone(), tokens }).unwrap();
+        let snapshots = storage.load().unwrap();
+        assert_eq!(snapshots.len(), 1);
+
+        let (id, snapshot) = &snapshots[0];
+        assert_eq!(id, &room_id);
+        assert_eq!(snapshot.tokens[&player], "token");
+        assert_eq!(snapshot.room.game.players()[0].hand(), room.game.players()[0].hand());
+        assert_eq!(snapshot.room.game.board().deck.len(), room.game.board().deck.len());
+
+        storage.delete(&room_id).unwrap();
+        assert!(storage.load().unwrap().is_empty());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}